@@ -1,88 +1,166 @@
 mod minesweeper;
-use std::io;
+use std::io::{self, IsTerminal};
 
 use minesweeper::*;
 
 fn main() {
+    // the language of the system first, so even the messages of the config are translated
+    set_language(Language::from_env(|name| std::env::var(name).ok()));
+    let mut config = load_config();
+    if let Some(language) = config.language.as_deref().and_then(Language::from_name) {
+        set_language(language);
+    }
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(err) = config.apply_args(&args) {
+        eprintln!("{err}");
+        std::process::exit(2);
+    }
+    if let Some(name) = &config.language {
+        match Language::from_name(name) {
+            Some(language) => set_language(language),
+            None => println!("{}", trf("There is no language called '{}', using the language of the system.", &[name])),
+        }
+    }
+    set_color_mode(config.color);
+    set_glyphs(config.glyphs);
+    set_coordinate_scheme(config.coords);
+    set_zoom(config.zoom);
+    set_accessible(config.accessible);
+    for warning in set_aliases(&config.aliases) {
+        println!("{warning}");
+    }
+    if let Some(name) = &config.theme {
+        match Theme::from_name(name) {
+            Some(theme) => set_theme(theme),
+            None => println!("{}", trf("There is no theme called '{}', using the classic colors.", &[name])),
+        }
+    }
     print_welcome();
 
+    // the full screen needs a real terminal, lines work everywhere
+    let mut full_screen = config.full_screen && !config.accessible && io::stdin().is_terminal() && io::stdout().is_terminal();
     let mut still_playing = true;
+    let mut rules = GameRules::default();
     while still_playing {
-        print_stats(&get_stats());
-        let (width, height) = get_size();
+        let stats = current_stats();
+        print_stats(&stats);
+        let (width, height) = get_size(&mut rules);
         if width == 0 || height == 0 {
             return; // quit game
         }
-        let mut mines = generate_map(width,height);
-        let mut first_guess = true;
+        let mut game = Game::new(width, height);
 
-        let (mut visible, mut remaing) = get_progress(&mines);
-        let mut all = remaing + visible;
-        while visible != all {
-            // show map    
-            println!("Progress: {visible}/{all}");
-            visualize_map(&mines, ' ', true);
-            // get input
-            let mut guess = String::new();
-            io::stdin().read_line(&mut guess)
-                .expect("Failed to read.");
+        let ending = if full_screen {
+            play_tui(&mut game, &rules, &stats).unwrap_or_else(|err| {
+                println!("{}", trf("The full screen mode is not working, switching to lines. {}", &[&err]));
+                full_screen = false;
+                play_lines(&mut game, &rules, &stats)
+            })
+        } else {
+            play_lines(&mut game, &rules, &stats)
+        };
 
-            // process input
-            if want_to_quit(&guess) {
-                still_playing = false;
-                let mut defused: usize = 0;
-                for row in &mines {
-                    defused += row.iter().filter(|tile| match tile {
-                        TileState::Marked(num) => *num < 0,
-                        _ => false
-                    }).count();
-                }
-                save_stats(defused, visible, false);
-                break;
-            } else if help(&guess) {
-                print_help();
-            } else if stat(&guess) {
-                print_stats(&get_stats());
-            } else if credits(&guess) {
-                print_credits();
-            } else if about(&guess) {
-                print_about();
-                print_credits();
-            } else if restart(&guess) {
-                break;
-            } else if first_guess {
-                while !process_input(&guess, &mut mines) {
-                    mines = generate_map(width,height);
-                }
-                first_guess = false;
-            } else {
-                if !process_input(&guess, &mut mines) {
-                    println!("That was a mine. Game over.");
-                    visualize_map(&mines, '*', true);
-                    // defused so far
-                    let mut defused: usize = 0;
-                    for row in &mines {
-                        defused += row.iter().filter(|tile| match tile {
-                            TileState::Marked(num) => *num < 0,
-                            _ => false
-                        }).count();
-                    }
-                    save_stats(defused, visible, true);
-                    still_playing = start_again();
-                    break;
-                }
-            }
+        game.finish(ending, &rules);
+        if matches!(ending, Ending::Won | Ending::Lost) {
+            auto_export(&game, ending == Ending::Won, &config);
+        }
+        match ending {
+            Ending::Won | Ending::Lost => still_playing = start_again(),
+            Ending::Quit => still_playing = false,
+            Ending::Restart => (),
+        }
+    }
+    print_stats(&current_stats());
+}
+
+/// The saved stats to show, when they can't be read that is told and the numbers are empty
+fn current_stats() -> Stats {
+    get_stats().unwrap_or_else(|err| {
+        println!("{}", trf("Failed to read the stats. {}", &[&err]));
+        Stats::default()
+    })
+}
+
+/// Plays the game by printing the map and reading the moves line by line
+///
+/// On a terminal every frame is drawn over the previous one, with the messages of the last move under the map.
+fn play_lines(game: &mut Game, rules: &GameRules, stats: &Stats) -> Ending {
+    let mut screen = LineScreen::new();
+    capture_messages(screen.in_place());
+    let ending = play_line_frames(game, rules, stats, &mut screen);
+    for message in take_messages() {
+        println!("{message}");
+    }
+    capture_messages(false);
+    ending
+}
 
-            (visible, remaing) = get_progress(&mines);
-            all = remaing + visible;
+fn play_line_frames(game: &mut Game, rules: &GameRules, stats: &Stats, screen: &mut LineScreen) -> Ending {
+    let mut messages: Vec<String> = Vec::new();
+    let (height, width) = (game.mines.len(), game.mines[0].len());
+    let mut view = Viewport::whole(height, width);
+    while !game.is_won() {
+        // show map, with the progress, the typed line and the messages around it
+        view.fit(height, width, 2 + messages.len().max(1));
+        let (visible, all) = game.progress();
+        let mut frame = if accessible() {
+            format!("{}\n", trf("{} of {} safe tiles are revealed.", &[&visible, &all]))
+        } else {
+            format!("{}\n{}", trf("Progress: {}/{}", &[&visible, &all]), map_text(&game.mines, ' ', true, game.last_move(), &view, &side_panel(game, stats)))
+        };
+        for message in &messages {
+            frame.push_str(message);
+            frame.push('\n');
         }
-        if visible == all {
-            println!("Success! All mines defused!");
-            visualize_map(&mines, 'X', false);
-            let total = mines.len() * mines[0].len();
-            save_stats(total - visible, visible, false);
-            still_playing = start_again();
+        screen.draw(&frame).expect("Failed to draw.");
+        // get input
+        let mut guess = String::new();
+        io::stdin().read_line(&mut guess)
+            .expect("Failed to read.");
+        screen.typed_line();
+
+        // process input
+        if want_to_quit(&guess) {
+            return Ending::Quit;
+        } else if help(&guess) {
+            print_help();
+            screen.keep();
+        } else if stat(&guess) {
+            print_stats(&current_stats());
+            screen.keep();
+        } else if credits(&guess) {
+            print_credits();
+            screen.keep();
+        } else if about(&guess) {
+            print_about();
+            print_credits();
+            screen.keep();
+        } else if restart(&guess) {
+            return Ending::Restart;
+        } else if theme_command(&guess) {
+            // the next frame is drawn with the new colors
+        } else if coords_command(&guess) {
+            // the next frame has the new names
+        } else if zoom_command(&guess) {
+            // the next frame is drawn with the new tiles
+        } else if pan_command(&guess, &mut view, height, width) {
+            // the next frame shows the new part of the map
+        } else if export_command(&guess, game) {
+            // the map is saved as it is now
+        } else if accessible_command(&guess, &game.mines) {
+            // the answer is in the messages
+        } else {
+            let before = game.mines.clone();
+            if !game.play(&guess, rules) {
+                return Ending::Lost;
+            }
+            if accessible() {
+                for sentence in announce_move(&before, &game.mines) {
+                    notify(sentence);
+                }
+            }
         }
+        messages = take_messages();
     }
-    print_stats(&get_stats());
+    Ending::Won
 }
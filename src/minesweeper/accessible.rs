@@ -0,0 +1,236 @@
+use std::sync::OnceLock;
+
+use super::coords::coordinate_scheme;
+use super::gameplay::notify;
+use super::locale::{tr, trf};
+use super::map_generator::{neighbours, tile_number, TileState};
+
+static DESCRIBE_COMMANDS: [&str; 2] = ["describe", "what"];
+static ROW_COMMANDS: [&str; 1] = ["row"];
+static FRONTIER_COMMANDS: [&str; 1] = ["frontier"];
+
+// the counted words with none, one and more of them, the languages count differently
+static FLAGGED_NEIGHBOURS: [&str; 3] = ["no flagged neighbours", "1 flagged neighbour", "{} flagged neighbours"];
+static HIDDEN_NEIGHBOURS: [&str; 3] = ["no hidden neighbours", "1 hidden neighbour", "{} hidden neighbours"];
+static ADJACENT_MINES: [&str; 3] = ["no adjacent mines", "1 adjacent mine", "{} adjacent mines"];
+static NUMBERS: [&str; 3] = ["no numbers", "1 number", "{} numbers"];
+static TILES: [&str; 3] = ["no tiles", "1 tile", "{} tiles"];
+static MINES: [&str; 3] = ["no mines", "1 mine", "{} mines"];
+static DEFUSERS: [&str; 3] = ["no defusers", "1 defuser", "{} defusers"];
+
+/// Sentences instead of the grid, for screen readers
+static ACCESSIBLE: OnceLock<bool> = OnceLock::new();
+
+pub fn set_accessible(on: bool) {
+    let _ = ACCESSIBLE.set(on);
+}
+
+pub fn accessible() -> bool {
+    ACCESSIBLE.get().copied().unwrap_or(false)
+}
+
+/// Like "no flagged neighbours", "1 flagged neighbour" or "2 flagged neighbours"
+fn count_of(count: usize, forms: &[&'static str; 3]) -> String {
+    match count {
+        0 => tr(forms[0]).to_string(),
+        1 => tr(forms[1]).to_string(),
+        _ => trf(forms[2], &[&count]),
+    }
+}
+
+fn is_hidden(tile: &TileState) -> bool {
+    matches!(tile, TileState::HiddenEmpty(_) | TileState::Mine | TileState::Question(_))
+}
+
+/// What the player knows about a tile, without giving away the mines
+fn tile_state_name(tile: &TileState) -> String {
+    match tile {
+        TileState::HiddenEmpty(_) | TileState::Mine => tr("hidden").to_string(),
+        TileState::Question(_) => tr("question mark").to_string(),
+        TileState::Marked(_) => tr("defused").to_string(),
+        TileState::Explosion => tr("exploded mine").to_string(),
+        TileState::VisibleEmpty(0) => tr("empty").to_string(),
+        TileState::VisibleEmpty(num) => trf("number {}", &[num]),
+    }
+}
+
+/// The state of a tile and its neighbours, like "revealed, 3 adjacent mines, 1 flagged neighbour, 2 hidden neighbours"
+fn tile_details(mine_map: &[Vec<TileState>], row: usize, column: usize) -> String {
+    let (height, width) = (mine_map.len(), mine_map[0].len());
+    let around = neighbours(row, column, height, width);
+    let flagged = around.iter().filter(|(row, column)| matches!(mine_map[*row][*column], TileState::Marked(_))).count();
+    let hidden = around.iter().filter(|(row, column)| is_hidden(&mine_map[*row][*column])).count();
+    let neighbourhood = format!("{}, {}", count_of(flagged, &FLAGGED_NEIGHBOURS), count_of(hidden, &HIDDEN_NEIGHBOURS));
+    match &mine_map[row][column] {
+        TileState::VisibleEmpty(num) => trf("revealed, {}, {}", &[&count_of(*num as usize, &ADJACENT_MINES), &neighbourhood]),
+        TileState::Marked(_) => trf("defused, {}", &[&neighbourhood]),
+        TileState::Question(_) => trf("hidden with a question mark, {}", &[&neighbourhood]),
+        TileState::Explosion => tr("exploded mine").to_string(),
+        TileState::HiddenEmpty(_) | TileState::Mine => trf("hidden, {}", &[&neighbourhood]),
+    }
+}
+
+/// A tile and its neighbours in one line, like "C4: revealed, 3 adjacent mines, 1 flagged neighbour, 2 hidden neighbours"
+pub fn describe_tile(mine_map: &[Vec<TileState>], row: usize, column: usize) -> String {
+    format!("{}: {}", coordinate_scheme().position_name(row, column), tile_details(mine_map, row, column))
+}
+
+/// The tiles of a row from left to right, the same tiles next to each other are told together
+pub fn describe_row(mine_map: &[Vec<TileState>], row: usize) -> String {
+    let scheme = coordinate_scheme();
+    let mut parts: Vec<String> = Vec::new();
+    let mut start = 0;
+    let line = &mine_map[row];
+    for column in 1..=line.len() {
+        let state = tile_state_name(&line[start]);
+        if column < line.len() && tile_state_name(&line[column]) == state {
+            continue;
+        }
+        if column - start == 1 {
+            parts.push(format!("{} {state}", scheme.position_name(row, start)));
+        } else {
+            parts.push(trf("{} to {} {}", &[&scheme.position_name(row, start), &scheme.position_name(row, column - 1), &state]));
+        }
+        start = column;
+    }
+    trf("Row {}: {}.", &[&scheme.row_name(row), &parts.join(", ")])
+}
+
+/// The revealed numbers which still have hidden tiles around them
+pub fn frontier(mine_map: &[Vec<TileState>]) -> Vec<(usize, usize)> {
+    let (height, width) = (mine_map.len(), mine_map[0].len());
+    let mut tiles = Vec::new();
+    for (row, line) in mine_map.iter().enumerate() {
+        for (column, tile) in line.iter().enumerate() {
+            if matches!(tile, TileState::VisibleEmpty(num) if *num > 0)
+                    && neighbours(row, column, height, width).iter().any(|(row, column)| is_hidden(&mine_map[*row][*column])) {
+                tiles.push((row, column));
+            }
+        }
+    }
+    tiles
+}
+
+fn describe_frontier(mine_map: &[Vec<TileState>]) -> String {
+    let scheme = coordinate_scheme();
+    let tiles = frontier(mine_map);
+    if tiles.is_empty() {
+        return tr("No revealed number has hidden tiles around it.").to_string();
+    }
+    let names: Vec<String> = tiles.iter()
+        .map(|(row, column)| format!("{} ({})", scheme.position_name(*row, *column), tile_number(&mine_map[*row][*column]).unwrap_or(0)))
+        .collect();
+    trf("{} on the frontier: {}.", &[&count_of(tiles.len(), &NUMBERS), &names.join(", ")])
+}
+
+/// Tells what a move changed on the map, in full sentences
+pub fn announce_move(before: &[Vec<TileState>], after: &[Vec<TileState>]) -> Vec<String> {
+    let scheme = coordinate_scheme();
+    let mut revealed = Vec::new();
+    let mut sentences = Vec::new();
+    for (row, (old_line, new_line)) in before.iter().zip(after).enumerate() {
+        for (column, (old, new)) in old_line.iter().zip(new_line).enumerate() {
+            if old == new {
+                continue;
+            }
+            let name = scheme.position_name(row, column);
+            match (old, new) {
+                (_, TileState::VisibleEmpty(_)) => revealed.push((row, column)),
+                (_, TileState::Explosion) => sentences.push(trf("{} was a mine.", &[&name])),
+                (_, TileState::Marked(_)) => sentences.push(trf("{} is defused.", &[&name])),
+                (_, TileState::Question(_)) => sentences.push(trf("{} is marked with a question mark.", &[&name])),
+                (TileState::Marked(_), _) => sentences.push(trf("The defuser is removed from {}.", &[&name])),
+                (TileState::Question(_), _) => sentences.push(trf("The question mark is removed from {}.", &[&name])),
+                _ => (),
+            }
+        }
+    }
+    match revealed[..] {
+        [] => (),
+        [(row, column)] => sentences.insert(0, trf("{} is {}.", &[&scheme.position_name(row, column), &tile_details(after, row, column)])),
+        _ => sentences.insert(0, trf("{} are revealed.", &[&count_of(revealed.len(), &TILES)])),
+    }
+    sentences
+}
+
+/// The end of the game told in sentences, instead of drawing the map
+pub fn describe_map(mine_map: &[Vec<TileState>], mine_char: char, show_revealed: bool) -> String {
+    let scheme = coordinate_scheme();
+    let names = |wanted: &dyn Fn(&TileState) -> bool| -> Vec<String> {
+        mine_map.iter().enumerate()
+            .flat_map(|(row, line)| line.iter().enumerate().filter(|(_, tile)| wanted(tile)).map(move |(column, _)| (row, column)))
+            .map(|(row, column)| scheme.position_name(row, column))
+            .collect()
+    };
+    let mut sentences = Vec::new();
+    if mine_char != ' ' {
+        let mines = names(&|tile| matches!(tile, TileState::Mine) || matches!(tile, TileState::Question(num) if *num < 0));
+        if !mines.is_empty() {
+            sentences.push(trf("{} left: {}.", &[&count_of(mines.len(), &MINES), &mines.join(", ")]));
+        }
+    }
+    let defused = names(&|tile| matches!(tile, TileState::Marked(num) if *num < 0));
+    sentences.push(trf("{} defused.", &[&count_of(defused.len(), &MINES)]));
+    if show_revealed {
+        let wrong = names(&|tile| matches!(tile, TileState::Marked(num) if *num >= 0));
+        if !wrong.is_empty() {
+            sentences.push(trf("{} on safe tiles: {}.", &[&count_of(wrong.len(), &DEFUSERS), &wrong.join(", ")]));
+        }
+    }
+    sentences.join(" ")
+}
+
+/// Handles 'describe C4', 'row C' and 'frontier', which tell about the map in words
+///
+/// Gives back `false` if the input is none of these.
+pub fn accessible_command(input: &str, mine_map: &[Vec<TileState>]) -> bool {
+    let scheme = coordinate_scheme();
+    let (height, width) = (mine_map.len(), mine_map[0].len());
+    let input = input.trim();
+    let (word, rest) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    let (word, rest) = (word.to_lowercase(), rest.trim());
+
+    if DESCRIBE_COMMANDS.contains(&word.as_str()) {
+        match scheme.parse(rest) {
+            Ok((row, column)) if (row as usize) < height && (column as usize) < width && scheme.looks_like_position(rest) =>
+                notify(format!("{}.", describe_tile(mine_map, row as usize, column as usize))),
+            _ => notify(trf("Type 'describe' with a position on the map, like 'describe {}'.", &[&scheme.position_name(2, 3)])),
+        }
+    } else if ROW_COMMANDS.contains(&word.as_str()) {
+        match (0..height).find(|row| scheme.row_name(*row).eq_ignore_ascii_case(rest)) {
+            Some(row) => notify(describe_row(mine_map, row)),
+            None => notify(trf("Type 'row' with the name of a row, from {} to {}.", &[&scheme.row_name(0), &scheme.row_name(height - 1)])),
+        }
+    } else if FRONTIER_COMMANDS.contains(&word.as_str()) && rest.is_empty() {
+        notify(describe_frontier(mine_map));
+    } else {
+        return false;
+    }
+    true
+}
+
+#[test]
+fn describe_tile_test() {
+    let mine_map = vec![
+        vec![TileState::Marked(-1), TileState::HiddenEmpty(1), TileState::Mine],
+        vec![TileState::VisibleEmpty(3), TileState::VisibleEmpty(3), TileState::HiddenEmpty(2)],
+        vec![TileState::Mine, TileState::VisibleEmpty(2), TileState::Question(1)],
+    ];
+    assert_eq!("B2: revealed, 3 adjacent mines, 1 flagged neighbour, 5 hidden neighbours", describe_tile(&mine_map, 1, 1));
+    assert_eq!("A3: hidden, no flagged neighbours, 2 hidden neighbours", describe_tile(&mine_map, 0, 2));
+    assert_eq!("Row A: A1 defused, A2 to A3 hidden.", describe_row(&mine_map, 0));
+    assert_eq!("Row B: B1 to B2 number 3, B3 hidden.", describe_row(&mine_map, 1));
+    assert_eq!(vec![(1, 0), (1, 1), (2, 1)], frontier(&mine_map));
+}
+
+#[test]
+fn announce_move_test() {
+    let before = vec![vec![TileState::HiddenEmpty(0), TileState::HiddenEmpty(1), TileState::Mine]];
+    let mut after = before.clone();
+    after[0][1] = TileState::VisibleEmpty(1);
+    assert_eq!(vec!["A2 is revealed, 1 adjacent mine, no flagged neighbours, 2 hidden neighbours.".to_string()],
+        announce_move(&before, &after));
+    after[0][0] = TileState::VisibleEmpty(0);
+    after[0][2] = TileState::Marked(-1);
+    assert_eq!(vec!["2 tiles are revealed.".to_string(), "A3 is defused.".to_string()], announce_move(&before, &after));
+}
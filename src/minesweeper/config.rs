@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use directories::ProjectDirs;
+
+use super::coords::CoordinateScheme;
+use super::export::ExportFormat;
+use super::locale::{trf, Language};
+use super::map_draw::{GlyphSet, Zoom};
+use super::theme::ColorMode;
+
+/// Settings of the player, read from `config.json` in the config folder
+///
+/// Every field is optional in the file, the missing ones get their default value.
+#[derive(Default, Debug, serde::Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Extra words for the commands, like `"f": "def"`
+    pub aliases: HashMap<String, String>,
+    /// Play on the full screen with a cursor instead of typing lines
+    pub full_screen: bool,
+    /// Sentences instead of the drawn map, for screen readers
+    pub accessible: bool,
+    /// The language of the texts, like `"hu"`, otherwise it comes from the locale of the system
+    pub language: Option<String>,
+    /// The color theme of the map, like `"deuteranopia-safe"`
+    pub theme: Option<String>,
+    /// Colors in the output: `"always"`, `"never"` or `"auto"`
+    pub color: ColorMode,
+    /// The characters of the map: `"ascii"`, `"unicode"` or `"emoji"`
+    pub glyphs: GlyphSet,
+    /// How the tiles are named: `"letters"` (C4), `"spreadsheet"` (D3) or `"numeric"` (3,4)
+    pub coords: CoordinateScheme,
+    /// How big the tiles are drawn: `"compact"`, `"normal"` or `"large"`
+    pub zoom: Zoom,
+    /// The files saved at the end of every won or lost game, like `["svg", "html"]`
+    pub auto_export: Vec<ExportFormat>,
+    /// Where the files of `auto_export` go, the current folder if it is not set
+    pub export_folder: Option<PathBuf>,
+    /// Put the moves of the game in the saved web pages, to step through them
+    pub export_steps: bool,
+}
+
+impl Config {
+    /// Applies the command line options on top of the config file
+    pub fn apply_args(&mut self, args: &[String]) -> Result<(), String> {
+        for arg in args {
+            match arg.as_str() {
+                "--line" => self.full_screen = false,
+                "--tui" => self.full_screen = true,
+                "--accessible" => self.accessible = true,
+                _ => {
+                    if let Some(mode) = arg.strip_prefix("--color=") {
+                        self.color = ColorMode::from_name(mode)
+                            .ok_or(trf("Unknown color mode '{}'. It can be 'always', 'never' or 'auto'.", &[&mode]))?;
+                        continue;
+                    }
+                    if let Some(language) = arg.strip_prefix("--lang=") {
+                        Language::from_name(language)
+                            .ok_or(trf("Unknown language '{}'. It can be 'en', 'hu' or 'de'.", &[&language]))?;
+                        self.language = Some(language.to_string());
+                        continue;
+                    }
+                    if let Some(set) = arg.strip_prefix("--glyphs=") {
+                        self.glyphs = GlyphSet::from_name(set)
+                            .ok_or(trf("Unknown glyphs '{}'. They can be 'ascii', 'unicode' or 'emoji'.", &[&set]))?;
+                        continue;
+                    }
+                    if let Some(scheme) = arg.strip_prefix("--coords=") {
+                        self.coords = CoordinateScheme::from_name(scheme)
+                            .ok_or(trf("Unknown coordinates '{}'. They can be 'letters', 'spreadsheet' or 'numeric'.", &[&scheme]))?;
+                        continue;
+                    }
+                    if let Some(zoom) = arg.strip_prefix("--zoom=") {
+                        self.zoom = Zoom::from_name(zoom)
+                            .ok_or(trf("Unknown zoom '{}'. It can be 'compact', 'normal' or 'large'.", &[&zoom]))?;
+                        continue;
+                    }
+                    return Err(trf("Unknown option '{}'. The options are '--line', '--tui', '--accessible', '--color=always|never|auto', '--lang=en|hu|de', '--glyphs=ascii|unicode|emoji', '--coords=letters|spreadsheet|numeric' and '--zoom=compact|normal|large'.", &[arg]));
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the config file, or gives back the default config if there is none
+pub fn load_config() -> Config {
+    let Some(proj_dirs) = ProjectDirs::from("com", "ChromaticCarrot",  "Minesweeper") else {
+        return Config::default();
+    };
+    let path = proj_dirs.config_dir().join("config.json");
+    if !path.exists() {
+        return Config::default();
+    }
+
+    match fs::read_to_string(&path) {
+        Ok(data) => parse_config(&data).unwrap_or_else(|err| {
+            println!("{}", trf("The config file {} is not valid, using the defaults. {}", &[&path.display(), &err]));
+            Config::default()
+        }),
+        Err(err) => {
+            println!("{}", trf("Error on reading the config file {}. {}", &[&path.display(), &err]));
+            Config::default()
+        }
+    }
+}
+
+fn parse_config(data: &str) -> Result<Config, serde_json::Error> {
+    serde_json::from_str(data)
+}
+
+#[test]
+fn parse_config_test() {
+    let config = parse_config(r#"{"aliases": {"f": "def", "r": "reveal"}}"#).unwrap();
+    assert_eq!(Some(&"def".to_string()), config.aliases.get("f"));
+    assert_eq!(2, config.aliases.len());
+
+    assert!(parse_config("{}").unwrap().aliases.is_empty());
+    assert!(parse_config(r#"{"aliases": ["f"]}"#).is_err());
+    assert!(parse_config(r#"{"full_screen": true}"#).unwrap().full_screen);
+    assert_eq!(Some("mono".to_string()), parse_config(r#"{"theme": "mono"}"#).unwrap().theme);
+    assert_eq!(ColorMode::Always, parse_config(r#"{"color": "always"}"#).unwrap().color);
+    assert_eq!(GlyphSet::Unicode, parse_config(r#"{"glyphs": "unicode"}"#).unwrap().glyphs);
+    assert_eq!(CoordinateScheme::Spreadsheet, parse_config(r#"{"coords": "spreadsheet"}"#).unwrap().coords);
+    assert_eq!(Zoom::Compact, parse_config(r#"{"zoom": "compact"}"#).unwrap().zoom);
+    assert_eq!(vec![ExportFormat::Svg], parse_config(r#"{"auto_export": ["svg"]}"#).unwrap().auto_export);
+    assert_eq!(vec![ExportFormat::Html], parse_config(r#"{"auto_export": ["html"]}"#).unwrap().auto_export);
+    assert!(parse_config(r#"{"auto_export": ["png"]}"#).is_err());
+}
+
+#[test]
+fn apply_args_test() {
+    let mut config = Config::default();
+    assert!(!config.full_screen);
+    assert_eq!(Ok(()), config.apply_args(&["--tui".to_string()]));
+    assert!(config.full_screen);
+    assert_eq!(Ok(()), config.apply_args(&["--line".to_string()]));
+    assert!(!config.full_screen);
+    assert_eq!(Ok(()), config.apply_args(&["--accessible".to_string()]));
+    assert!(config.accessible);
+    assert_eq!(Ok(()), config.apply_args(&["--lang=de".to_string()]));
+    assert_eq!(Some("de".to_string()), config.language);
+    assert!(config.apply_args(&["--lang=xx".to_string()]).is_err());
+    assert!(config.apply_args(&["--fast".to_string()]).is_err());
+    assert_eq!(ColorMode::Auto, config.color);
+    assert_eq!(Ok(()), config.apply_args(&["--color=never".to_string()]));
+    assert_eq!(ColorMode::Never, config.color);
+    assert!(config.apply_args(&["--color=pink".to_string()]).is_err());
+    assert_eq!(Ok(()), config.apply_args(&["--glyphs=emoji".to_string()]));
+    assert_eq!(GlyphSet::Emoji, config.glyphs);
+    assert_eq!(Ok(()), config.apply_args(&["--coords=numeric".to_string()]));
+    assert_eq!(CoordinateScheme::Numeric, config.coords);
+    assert_eq!(Ok(()), config.apply_args(&["--zoom=large".to_string()]));
+    assert_eq!(Zoom::Large, config.zoom);
+    assert!(config.apply_args(&["--zoom=huge".to_string()]).is_err());
+}
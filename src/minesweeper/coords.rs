@@ -0,0 +1,205 @@
+use std::io::ErrorKind;
+use std::sync::Mutex;
+
+use regex::Regex;
+
+use super::gameplay::notify;
+use super::locale::trf;
+use super::map_draw::{add_row_number, get_column_number, get_row_number};
+
+static COORDS_COMMANDS: [&str; 2] = ["coords", "coordinates"];
+
+/// How the rows and the columns are named, on the map and in the moves
+#[derive(Clone, Copy, PartialEq, Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoordinateScheme {
+    /// Letters for the rows and numbers for the columns, like "C4"
+    #[default]
+    Letters,
+    /// Letters for the columns and numbers for the rows, like in a spreadsheet: "D3"
+    Spreadsheet,
+    /// Numbers for both, the row first: "3,4"
+    Numeric,
+}
+
+static ALL_SCHEMES: [CoordinateScheme; 3] = [CoordinateScheme::Letters, CoordinateScheme::Spreadsheet, CoordinateScheme::Numeric];
+
+/// A position as it was typed, even if it is outside of every map
+///
+/// The rows and the columns are counted from 1 here, so a typed 0 is kept as 0.
+#[derive(PartialEq, Debug)]
+pub struct TypedPosition {
+    pub row: usize,
+    pub column: usize,
+    pub row_text: String,
+    pub column_text: String,
+}
+
+impl CoordinateScheme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CoordinateScheme::Letters => "letters",
+            CoordinateScheme::Spreadsheet => "spreadsheet",
+            CoordinateScheme::Numeric => "numeric",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<CoordinateScheme> {
+        ALL_SCHEMES.iter().copied().find(|scheme| scheme.name() == name.trim().to_lowercase())
+    }
+
+    pub fn row_name(&self, row: usize) -> String {
+        match self {
+            CoordinateScheme::Letters => add_row_number(row as u8),
+            _ => (row + 1).to_string(),
+        }
+    }
+
+    pub fn column_name(&self, column: usize) -> String {
+        match self {
+            CoordinateScheme::Spreadsheet => add_row_number(column as u8),
+            _ => (column + 1).to_string(),
+        }
+    }
+
+    /// The name of a tile the player can type, like "C4"
+    pub fn position_name(&self, row: usize, column: usize) -> String {
+        match self {
+            CoordinateScheme::Letters => format!("{}{}", self.row_name(row), self.column_name(column)),
+            CoordinateScheme::Spreadsheet => format!("{}{}", self.column_name(column), self.row_name(row)),
+            CoordinateScheme::Numeric => format!("{},{}", self.row_name(row), self.column_name(column)),
+        }
+    }
+
+    /// What a position is made of, to explain it to the player
+    pub fn explanation(&self) -> &'static str {
+        match self {
+            CoordinateScheme::Letters => "the row letter and the column number",
+            CoordinateScheme::Spreadsheet => "the column letter and the row number",
+            CoordinateScheme::Numeric => "the row and the column number with a comma",
+        }
+    }
+
+    pub fn looks_like_position(&self, input: &str) -> bool {
+        let pattern = match self {
+            CoordinateScheme::Numeric => r"^[0-9]+\s*,\s*[0-9]+$",
+            _ => "^[0-9]+[a-zA-Z]+$|^[a-zA-Z]+[0-9]+$",
+        };
+        Regex::new(pattern).unwrap().is_match(input.trim())
+    }
+
+    /// Reads a position as row and column, both from 0
+    pub fn parse(&self, input: &str) -> Result<(u8, u8), ErrorKind> {
+        let letters: String = input.chars().filter(|c| c.is_alphabetic()).collect();
+        let digits: String = input.chars().filter(|c| c.is_numeric()).collect();
+        match self {
+            CoordinateScheme::Letters => Ok((get_row_number(&letters)?, get_column_number(&digits)?)),
+            CoordinateScheme::Spreadsheet => Ok((get_column_number(&digits)?, get_row_number(&letters)?)),
+            CoordinateScheme::Numeric => {
+                let (row, column) = input.trim().split_once(',').ok_or(ErrorKind::InvalidInput)?;
+                Ok((get_column_number(row.trim())?, get_column_number(column.trim())?))
+            },
+        }
+    }
+
+    /// Reads a position which looks right, without checking if it is on a map
+    pub fn read_loosely(&self, input: &str) -> Option<TypedPosition> {
+        if !self.looks_like_position(input) {
+            return None;
+        }
+        let number = |text: &str| text.chars()
+            .filter(|ch| ch.is_ascii_digit())
+            .fold(0usize, |sum, digit| sum.saturating_mul(10).saturating_add(digit as usize - '0' as usize));
+        let letters: String = input.chars().filter(|ch| ch.is_ascii_alphabetic()).collect::<String>().to_uppercase();
+        let letter_number = letters.chars()
+            .fold(0usize, |sum, letter| sum.saturating_mul(26).saturating_add(letter as usize - 'A' as usize + 1));
+        let digits: String = input.chars().filter(|ch| ch.is_ascii_digit()).collect();
+
+        Some(match self {
+            CoordinateScheme::Letters => TypedPosition {
+                row: letter_number, column: number(&digits), row_text: letters, column_text: number(&digits).to_string(),
+            },
+            CoordinateScheme::Spreadsheet => TypedPosition {
+                row: number(&digits), column: letter_number, row_text: number(&digits).to_string(), column_text: letters,
+            },
+            CoordinateScheme::Numeric => {
+                let (row, column) = input.trim().split_once(',')?;
+                TypedPosition {
+                    row: number(row), column: number(column), row_text: number(row).to_string(), column_text: number(column).to_string(),
+                }
+            },
+        })
+    }
+}
+
+/// The coordinates of the game, they can be changed while playing
+static SCHEME: Mutex<CoordinateScheme> = Mutex::new(CoordinateScheme::Letters);
+
+pub fn coordinate_scheme() -> CoordinateScheme {
+    *SCHEME.lock().unwrap()
+}
+
+pub fn set_coordinate_scheme(scheme: CoordinateScheme) {
+    *SCHEME.lock().unwrap() = scheme;
+}
+
+/// Handles the 'coords' command: alone it lists the schemes, with a name it switches to that one
+///
+/// Gives back `false` if the input is not a coords command.
+pub fn coords_command(input: &str) -> bool {
+    let input = input.trim();
+    let (word, name) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    if !COORDS_COMMANDS.contains(&&word.to_lowercase()[..]) {
+        return false;
+    }
+    if name.trim().is_empty() {
+        let names: Vec<String> = ALL_SCHEMES.iter()
+            .map(|scheme| format!("{} ({})", scheme.name(), scheme.position_name(2, 3)))
+            .collect();
+        notify(trf("The coordinates are '{}'. They can be: {}.", &[&coordinate_scheme().name(), &names.join(", ")]));
+        return true;
+    }
+    match CoordinateScheme::from_name(name) {
+        Some(scheme) => {
+            set_coordinate_scheme(scheme);
+            notify(trf("The coordinates are '{}' now, like '{}'.", &[&scheme.name(), &scheme.position_name(2, 3)]));
+        },
+        None => notify(trf("There are no coordinates called '{}'. Type 'coords' to see them.", &[&name.trim()])),
+    }
+    true
+}
+
+#[test]
+fn names_test() {
+    assert_eq!("C4", CoordinateScheme::Letters.position_name(2, 3));
+    assert_eq!("D3", CoordinateScheme::Spreadsheet.position_name(2, 3));
+    assert_eq!("3,4", CoordinateScheme::Numeric.position_name(2, 3));
+    assert_eq!("AA", CoordinateScheme::Spreadsheet.column_name(26));
+    assert_eq!("27", CoordinateScheme::Spreadsheet.row_name(26));
+    assert_eq!(Some(CoordinateScheme::Numeric), CoordinateScheme::from_name("Numeric"));
+}
+
+#[test]
+fn parse_test() {
+    for scheme in ALL_SCHEMES {
+        let name = scheme.position_name(27, 4);
+        assert!(scheme.looks_like_position(&name), "{name}");
+        assert_eq!(Ok((27, 4)), scheme.parse(&name), "{name}");
+    }
+    assert_eq!(Ok((3, 1)), CoordinateScheme::Spreadsheet.parse("b4"));
+    assert_eq!(Ok((3, 1)), CoordinateScheme::Numeric.parse("4 , 2"));
+    assert!(CoordinateScheme::Numeric.parse("4").is_err());
+    assert!(CoordinateScheme::Numeric.parse("0,2").is_err());
+    assert!(!CoordinateScheme::Numeric.looks_like_position("b4"));
+    assert!(!CoordinateScheme::Letters.looks_like_position("4,2"));
+}
+
+#[test]
+fn read_loosely_test() {
+    let typed = CoordinateScheme::Spreadsheet.read_loosely("zz0").unwrap();
+    assert_eq!((0, 702), (typed.row, typed.column));
+    assert_eq!("ZZ", typed.column_text);
+    let typed = CoordinateScheme::Numeric.read_loosely("300,2").unwrap();
+    assert_eq!((300, 2), (typed.row, typed.column));
+    assert_eq!(None, CoordinateScheme::Letters.read_loosely("a,3"));
+}
@@ -0,0 +1,407 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crossterm::style::{Attribute, Color, StyledContent};
+
+use super::config::Config;
+use super::coords::coordinate_scheme;
+use super::game::Game;
+use super::gameplay::notify;
+use super::locale::{tr, trf};
+use super::map_draw::styled_tile;
+use super::map_generator::{count_mines, TileState};
+use super::stats::GameResult;
+use super::theme::{current_theme, style_of, ColorDepth, Look};
+
+static EXPORT_COMMANDS: [&str; 1] = ["export"];
+
+/// The kinds of files the map can be saved as
+#[derive(Clone, Copy, PartialEq, Debug, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Svg,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn from_name(name: &str) -> Option<ExportFormat> {
+        match name.trim().to_lowercase().as_str() {
+            "svg" => Some(ExportFormat::Svg),
+            "html" => Some(ExportFormat::Html),
+            _ => None,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Svg => "svg",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+const TILE_SIZE: usize = 24;
+const MARGIN: usize = 32;
+const BACKGROUND: &str = "#1e1e1e";
+const HIDDEN_TILE: &str = "#4a4a4a";
+const REVEALED_TILE: &str = "#2a2a2a";
+const TEXT: &str = "#d4d4d4";
+
+/// The colors of the theme for an image, like on a true color terminal
+fn image_paint(text: String, look: Look) -> StyledContent<String> {
+    StyledContent::new(style_of(look, current_theme(), ColorDepth::TrueColor), text)
+}
+
+/// The color for the web, the basic terminal colors get the usual VGA shades
+fn css_color(color: Color) -> String {
+    let hex = match color {
+        Color::Rgb { r, g, b } => return format!("#{r:02x}{g:02x}{b:02x}"),
+        Color::Black => "#000000",
+        Color::DarkGrey => "#808080",
+        Color::Red => "#ff5555",
+        Color::DarkRed => "#aa0000",
+        Color::Green => "#55ff55",
+        Color::DarkGreen => "#00aa00",
+        Color::Yellow => "#ffff55",
+        Color::DarkYellow => "#aa5500",
+        Color::Blue => "#5555ff",
+        Color::DarkBlue => "#0000aa",
+        Color::Magenta => "#ff55ff",
+        Color::DarkMagenta => "#aa00aa",
+        Color::Cyan => "#55ffff",
+        Color::DarkCyan => "#00aaaa",
+        Color::White => "#ffffff",
+        Color::Grey => "#aaaaaa",
+        Color::Reset | Color::AnsiValue(_) => TEXT,
+    };
+    hex.to_string()
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Tells if the tile looks hidden on the map
+fn looks_hidden(tile: &TileState, mine_char: char) -> bool {
+    match tile {
+        TileState::HiddenEmpty(_) | TileState::Marked(_) | TileState::Question(_) => true,
+        TileState::Mine => mine_char == ' ',
+        _ => false,
+    }
+}
+
+/// How a tile looks on an image: its text, colors and font
+#[derive(Clone, PartialEq, Debug)]
+struct ImageTile {
+    text: String,
+    background: String,
+    foreground: String,
+    bold: bool,
+    underlined: bool,
+}
+
+fn image_tile(tile: &TileState, mine_char: char, show_revealed: bool) -> ImageTile {
+    let token = styled_tile(tile, mine_char, show_revealed, &image_paint);
+    let style = token.style();
+    let mut background = style.background_color.map(css_color)
+        .unwrap_or_else(|| (if looks_hidden(tile, mine_char) { HIDDEN_TILE } else { REVEALED_TILE }).to_string());
+    let mut foreground = style.foreground_color.map(css_color).unwrap_or_else(|| TEXT.to_string());
+    if style.attributes.has(Attribute::Reverse) {
+        std::mem::swap(&mut background, &mut foreground);
+    }
+    ImageTile {
+        text: token.content().trim().to_string(),
+        background,
+        foreground,
+        bold: style.attributes.has(Attribute::Bold),
+        underlined: style.attributes.has(Attribute::Underlined),
+    }
+}
+
+/// The mine character and the revealing of the map at the end of the game, like `Game::finish` draws it
+fn final_look(result: Option<GameResult>) -> (char, bool) {
+    match result {
+        Some(GameResult::Won) => ('X', false),
+        Some(GameResult::Lost) => ('*', true),
+        _ => (' ', true),
+    }
+}
+
+/// Draws the map as an SVG image, with the same texts and colors as on the terminal
+pub fn svg_image(mine_map: &[Vec<TileState>], mine_char: char, show_revealed: bool) -> String {
+    let scheme = coordinate_scheme();
+    let (height, width) = (mine_map.len(), mine_map[0].len());
+    let image_width = 2 * MARGIN + width * TILE_SIZE;
+    let image_height = 2 * MARGIN + height * TILE_SIZE;
+    let middle = |index: usize| MARGIN + index * TILE_SIZE + TILE_SIZE / 2;
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{image_width}" height="{image_height}" viewBox="0 0 {image_width} {image_height}">"#);
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="{BACKGROUND}"/>"#);
+    let _ = writeln!(svg, r#"<g font-family="monospace" font-size="16" text-anchor="middle" dominant-baseline="central">"#);
+
+    // the names of the rows and the columns on every side, like on the map
+    for column in 0..width {
+        let name = escape_xml(&scheme.column_name(column));
+        for y in [MARGIN / 2, image_height - MARGIN / 2] {
+            let _ = writeln!(svg, r#"<text x="{}" y="{y}" fill="{TEXT}">{name}</text>"#, middle(column));
+        }
+    }
+    for row in 0..height {
+        let name = escape_xml(&scheme.row_name(row));
+        for x in [MARGIN / 2, image_width - MARGIN / 2] {
+            let _ = writeln!(svg, r#"<text x="{x}" y="{}" fill="{TEXT}">{name}</text>"#, middle(row));
+        }
+    }
+
+    for (row, line) in mine_map.iter().enumerate() {
+        for (column, tile) in line.iter().enumerate() {
+            let look = image_tile(tile, mine_char, show_revealed);
+            let _ = writeln!(svg, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                MARGIN + column * TILE_SIZE + 1, MARGIN + row * TILE_SIZE + 1, TILE_SIZE - 2, TILE_SIZE - 2, look.background);
+            if look.text.is_empty() {
+                continue;
+            }
+            let mut decoration = String::new();
+            if look.bold {
+                decoration.push_str(r#" font-weight="bold""#);
+            }
+            if look.underlined {
+                decoration.push_str(r#" text-decoration="underline""#);
+            }
+            let _ = writeln!(svg, r#"<text x="{}" y="{}" fill="{}"{decoration}>{}</text>"#,
+                middle(column), middle(row), look.foreground, escape_xml(&look.text));
+        }
+    }
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+/// Gives back the number of the look, adding it to the list if it is new
+fn look_index(looks: &mut Vec<ImageTile>, look: ImageTile) -> usize {
+    looks.iter().position(|known| *known == look).unwrap_or_else(|| {
+        looks.push(look);
+        looks.len() - 1
+    })
+}
+
+/// The looks of the tiles of a map, row by row
+fn frame(looks: &mut Vec<ImageTile>, mine_map: &[Vec<TileState>], mine_char: char, show_revealed: bool) -> Vec<usize> {
+    mine_map.iter().flatten().map(|tile| look_index(looks, image_tile(tile, mine_char, show_revealed))).collect()
+}
+
+/// JSON for an inline script, a typed move can't end the script early
+fn script_json<T: serde::Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default().replace("</", "<\\/")
+}
+
+/// A standalone web page with the map as a table and the summary of the game
+///
+/// With `with_steps` it has buttons to step through the moves of the game.
+pub fn html_page(game: &Game, result: Option<GameResult>, with_steps: bool) -> String {
+    let scheme = coordinate_scheme();
+    let (mine_char, show_revealed) = final_look(result);
+    let mine_map = &game.mines;
+    let (height, width) = (mine_map.len(), mine_map[0].len());
+
+    // every different look of a tile gets a class, the steps only need the numbers of those
+    let mut looks: Vec<ImageTile> = Vec::new();
+    let board = frame(&mut looks, mine_map, mine_char, show_revealed);
+    let steps = if with_steps { game.steps() } else { Vec::new() };
+    let frames: Vec<Vec<usize>> = if with_steps {
+        let mut frames: Vec<Vec<usize>> = steps.iter().map(|step| frame(&mut looks, &step.mines, ' ', true)).collect();
+        frames.push(board.clone());
+        frames
+    } else {
+        Vec::new()
+    };
+
+    let result_text = match result {
+        Some(GameResult::Won) => "won",
+        Some(GameResult::Lost) => "lost",
+        Some(GameResult::Abandoned) => "abandoned",
+        None => "still playing",
+    };
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(html, "<title>Minesweeper {width}x{height}</title>");
+    html.push_str("<style>\n");
+    let _ = writeln!(html, "body {{ background: {BACKGROUND}; color: {TEXT}; font-family: monospace; }}");
+    html.push_str("table { border-spacing: 2px; }\n");
+    let _ = writeln!(html, "td, th {{ width: {TILE_SIZE}px; height: {TILE_SIZE}px; padding: 0; text-align: center; font-weight: normal; }}");
+    for (index, look) in looks.iter().enumerate() {
+        let _ = write!(html, ".t{index} {{ background: {}; color: {};", look.background, look.foreground);
+        if look.bold {
+            html.push_str(" font-weight: bold;");
+        }
+        if look.underlined {
+            html.push_str(" text-decoration: underline;");
+        }
+        html.push_str(" }\n");
+    }
+    html.push_str("</style>\n</head>\n<body>\n<h1>Minesweeper</h1>\n<ul>\n");
+    let _ = writeln!(html, "<li>Size: {width}x{height}</li>");
+    let _ = writeln!(html, "<li>Mines: {}</li>", count_mines(mine_map));
+    let _ = writeln!(html, "<li>Time: {:.1}s</li>", game.duration().as_secs_f64());
+    let _ = writeln!(html, "<li>Result: {result_text}</li>");
+    html.push_str("</ul>\n<table id=\"board\">\n");
+
+    let column_names: String = (0..width)
+        .map(|column| format!("<th>{}</th>", escape_xml(&scheme.column_name(column))))
+        .collect();
+    let header = format!("<tr><th></th>{column_names}<th></th></tr>\n");
+    html.push_str(&header);
+    for row in 0..height {
+        let name = escape_xml(&scheme.row_name(row));
+        let _ = write!(html, "<tr><th>{name}</th>");
+        for index in &board[row * width..(row + 1) * width] {
+            let _ = write!(html, "<td class=\"t{index}\">{}</td>", escape_xml(&looks[*index].text));
+        }
+        let _ = writeln!(html, "<th>{name}</th></tr>");
+    }
+    html.push_str(&header);
+    html.push_str("</table>\n");
+
+    if with_steps {
+        let texts: Vec<&str> = looks.iter().map(|look| look.text.as_str()).collect();
+        let moves: Vec<&str> = steps.iter().map(|step| step.guess.as_str()).collect();
+        let last = if result.is_some() { "The end of the game" } else { "The map at the export" };
+        html.push_str("<p><button id=\"previous\">&lt;</button> <button id=\"next\">&gt;</button> <span id=\"step\"></span></p>\n<script>\n");
+        let _ = writeln!(html, "const texts = {};", script_json(&texts));
+        let _ = writeln!(html, "const moves = {};", script_json(&moves));
+        let _ = writeln!(html, "const frames = {};", script_json(&frames));
+        let _ = writeln!(html, "const last = {};", script_json(last));
+        html.push_str(STEP_SCRIPT);
+        html.push_str("</script>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Shows a frame of the moves on the table, the buttons go back and forth
+static STEP_SCRIPT: &str = r##"const cells = document.querySelectorAll("#board td");
+let shown = frames.length - 1;
+function show(index) {
+    shown = Math.max(0, Math.min(frames.length - 1, index));
+    frames[shown].forEach((look, cell) => {
+        cells[cell].className = "t" + look;
+        cells[cell].textContent = texts[look];
+    });
+    document.getElementById("step").textContent = shown < moves.length
+        ? `Move ${shown + 1} of ${moves.length}: ${moves[shown]}`
+        : last;
+}
+document.getElementById("previous").onclick = () => show(shown - 1);
+document.getElementById("next").onclick = () => show(shown + 1);
+show(shown);
+"##;
+
+fn write_export(format: ExportFormat, path: &Path, game: &Game, result: Option<GameResult>, with_steps: bool) -> io::Result<()> {
+    let (mine_char, show_revealed) = final_look(result);
+    let content = match format {
+        ExportFormat::Svg => svg_image(&game.mines, mine_char, show_revealed),
+        ExportFormat::Html => html_page(game, result, with_steps),
+    };
+    fs::write(path, content)
+}
+
+fn report_export(path: &Path, written: io::Result<()>) {
+    match written {
+        Ok(()) => notify(trf("The map is saved to {}.", &[&path.display()])),
+        Err(err) => notify(trf("Could not save the map to {}. {}", &[&path.display(), &err])),
+    }
+}
+
+/// Handles the 'export' command, like "export svg map.svg" or "export html steps game.html"
+///
+/// Gives back `false` if the input is not an export command.
+pub fn export_command(input: &str, game: &Game) -> bool {
+    let input = input.trim();
+    let (word, rest) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    if !EXPORT_COMMANDS.contains(&&word.to_lowercase()[..]) {
+        return false;
+    }
+    let (format, mut file) = rest.trim().split_once(char::is_whitespace).unwrap_or((rest.trim(), ""));
+    let format = ExportFormat::from_name(format);
+    let mut with_steps = false;
+    if let (Some(ExportFormat::Html), Some(("steps", name))) = (format, file.trim().split_once(char::is_whitespace)) {
+        (with_steps, file) = (true, name);
+    }
+    let (Some(format), false) = (format, file.trim().is_empty()) else {
+        notify(tr("Type 'export svg' or 'export html' with the name of the file, like 'export svg map.svg'. With 'export html steps' the page can step through the moves."));
+        return true;
+    };
+
+    let path = PathBuf::from(file.trim());
+    report_export(&path, write_export(format, &path, game, None, with_steps));
+    true
+}
+
+/// Saves the map at the end of a game in the formats of the config, into the folder of the config or the current one
+pub fn auto_export(game: &Game, won: bool, config: &Config) {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs());
+    let result = if won { GameResult::Won } else { GameResult::Lost };
+    for format in &config.auto_export {
+        let name = format!("minesweeper-{seconds}-{}.{}", if won { "won" } else { "lost" }, format.extension());
+        let path = config.export_folder.as_ref().map_or_else(|| PathBuf::from(&name), |folder| folder.join(&name));
+        report_export(&path, write_export(*format, &path, game, Some(result), config.export_steps));
+    }
+}
+
+#[test]
+fn css_color_test() {
+    assert_eq!("#5078ff", css_color(Color::Rgb { r: 80, g: 120, b: 255 }));
+    assert_eq!("#55ff55", css_color(Color::Green));
+}
+
+#[test]
+fn svg_image_test() {
+    let mine_map = vec![
+        vec![TileState::VisibleEmpty(1), TileState::Explosion],
+        vec![TileState::Marked(-1), TileState::HiddenEmpty(1)],
+    ];
+    let svg = svg_image(&mine_map, '*', true);
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.ends_with("</svg>\n"));
+    // the number in the color of the theme, and the explosion on red
+    assert!(svg.contains(r##"fill="#5078ff">1</text>"##));
+    assert!(svg.contains(r##"fill="#dc0000"/>"##));
+    // the names of the rows and columns
+    assert!(svg.contains(">B</text>"));
+    assert!(svg.contains(">2</text>"));
+}
+
+#[test]
+fn html_page_test() {
+    let mut game = Game::new(2, 1);
+    game.mines = vec![vec![TileState::VisibleEmpty(1), TileState::Explosion]];
+    let page = html_page(&game, Some(GameResult::Lost), false);
+    assert!(page.contains("<li>Size: 2x1</li>"));
+    assert!(page.contains("<li>Mines: 1</li>"));
+    assert!(page.contains("<li>Result: lost</li>"));
+    assert!(page.contains("color: #5078ff;"));
+    assert!(page.contains("<tr><th>A</th><td class=\"t0\">1</td>"));
+    assert!(!page.contains("<script>"));
+    assert!(html_page(&game, Some(GameResult::Lost), true).contains("const frames = [[0,1]];"));
+}
+
+#[test]
+fn export_command_test() {
+    let mut game = Game::new(2, 1);
+    game.mines = vec![vec![TileState::VisibleEmpty(0), TileState::HiddenEmpty(0)]];
+    let path = std::env::temp_dir().join("minesweeper_export_command_test.svg");
+    assert!(export_command(&format!("export svg {}", path.display()), &game));
+    assert!(fs::read_to_string(&path).unwrap().contains("<svg"));
+    let _ = fs::remove_file(&path);
+
+    let path = std::env::temp_dir().join("minesweeper_export_command_test.html");
+    assert!(export_command(&format!("export html steps {}", path.display()), &game));
+    assert!(fs::read_to_string(&path).unwrap().contains("<script>"));
+    let _ = fs::remove_file(&path);
+
+    assert!(export_command("export png", &game));
+    assert!(!export_command("a1", &game));
+}
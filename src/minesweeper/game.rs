@@ -0,0 +1,349 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use super::gameplay::*;
+use super::map_draw::*;
+use super::map_generator::*;
+use super::accessible::accessible;
+use super::coords::coordinate_scheme;
+use super::locale::{tr, trf};
+use super::solver::{analyse_fatal_move, FatalMove};
+use super::history::HistoryRecord;
+use super::stats::*;
+
+/// How a game (or a screen of it) ended
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Ending {
+    Won,
+    Lost,
+    Quit,
+    Restart,
+}
+
+/// A move of the player and the map after it
+pub struct Step {
+    pub guess: String,
+    pub mines: Vec<Vec<TileState>>,
+}
+
+/// A move of the player and the tiles it changed, the maps are only made for an export
+struct Change {
+    guess: String,
+    tiles: Vec<(usize, usize, TileState)>,
+}
+
+/// The state of the game being played
+pub struct Game {
+    pub mines: Vec<Vec<TileState>>,
+    width: u8,
+    height: u8,
+    first_guess: bool,
+    clicks: usize,
+    started: Option<Instant>,
+    rewinds: usize,
+    flagged: bool,
+    hints: usize,
+    /// The seed of the map, the same seed and size give the same map
+    seed: u64,
+    changes: Vec<Change>,
+    /// Where the game was lost, and how bad that move was
+    fatal_move: Option<(usize, usize, FatalMove)>,
+    /// The last move which changed the map, it is highlighted on the map
+    last_move: Option<MoveReport>,
+}
+
+impl Game {
+    pub fn new(width: u8, height: u8) -> Game {
+        let seed = rand::random();
+        Game {
+            mines: generate_map(width, height, seed),
+            width,
+            height,
+            first_guess: true,
+            clicks: 0,
+            started: None,
+            rewinds: 0,
+            flagged: false,
+            hints: 0,
+            seed,
+            changes: Vec::new(),
+            fatal_move: None,
+            last_move: None,
+        }
+    }
+
+    /// Revealed and total number of safe tiles
+    pub fn progress(&self) -> (usize, usize) {
+        let (visible, remaining) = get_progress(&self.mines);
+        (visible, visible + remaining)
+    }
+
+    pub fn is_won(&self) -> bool {
+        let (visible, all) = self.progress();
+        visible == all
+    }
+
+    /// Applies a move of the player, and tells if it was a mine
+    ///
+    /// In training mode the explosion is rewound, so it always returns `true`.
+    pub fn play(&mut self, guess: &str, rules: &GameRules) -> bool {
+        // flags are refused in no flag mode, those don't count
+        let move_type = translate_move(guess);
+        let flag_move = move_type.uses_flag();
+        if flag_move && !rules.no_flag {
+            self.flagged = true;
+        }
+        if move_type.is_click() && !(flag_move && rules.no_flag) {
+            self.clicks += 1;
+        }
+        if move_type.is_hint() {
+            self.hints += 1;
+        }
+
+        if self.first_guess {
+            let mut report = process_input(guess, move_type, &mut self.mines, rules);
+            while !report.safe {
+                self.seed = rand::random();
+                self.mines = generate_map(self.width, self.height, self.seed);
+                report = process_input(guess, move_type, &mut self.mines, rules);
+            }
+            self.first_guess = false;
+            self.record_move(report);
+            self.started = Some(Instant::now());
+            self.add_change(guess, &generate_map(self.width, self.height, self.seed));
+            return true;
+        }
+
+        let before = self.mines.clone();
+        let report = process_input(guess, move_type, &mut self.mines, rules);
+        let safe = report.safe;
+        if self.mines != before {
+            self.add_change(guess, &before);
+            self.record_move(report);
+        }
+        if safe {
+            return true;
+        }
+        let fatal_move = find_explosion(&self.mines).map(|(row, column)| (row, column, analyse_fatal_move(&before, row, column)));
+        if !rules.training {
+            self.fatal_move = fatal_move;
+            return false;
+        }
+
+        self.rewinds += 1;
+        notify(tr("That was a mine. Rewinding the move."));
+        if let Some((_, _, fatal_move)) = fatal_move {
+            notify(fatal_move.describe());
+        }
+        // the step back is a step of its own, the next move starts from here
+        let exploded = self.mines.clone();
+        self.mines = before;
+        self.add_change("rewind", &exploded);
+        self.last_move = None;
+        true
+    }
+
+    /// Remembers the move for the highlight, and tells how many tiles it opened
+    fn record_move(&mut self, report: MoveReport) {
+        if report.opened.len() > 1 && !accessible() {
+            notify(trf("Opened {} tiles.", &[&report.opened.len()]));
+        }
+        self.last_move = Some(report);
+    }
+
+    /// The last move which changed the map
+    pub fn last_move(&self) -> Option<&MoveReport> {
+        self.last_move.as_ref()
+    }
+
+    /// Remembers the tiles changed by the move, compared to the map before it
+    fn add_change(&mut self, guess: &str, before: &[Vec<TileState>]) {
+        let tiles = self.mines.iter().zip(before).enumerate()
+            .flat_map(|(row, (line, old_line))| {
+                line.iter().zip(old_line).enumerate()
+                    .filter(|(_, (tile, old_tile))| tile != old_tile)
+                    .map(move |(column, (tile, _))| (row, column, tile.clone()))
+            })
+            .collect();
+        self.changes.push(Change { guess: guess.trim().to_string(), tiles });
+    }
+
+    /// The moves which changed the map, in order, replayed from the map of the seed
+    pub fn steps(&self) -> Vec<Step> {
+        let mut mines = generate_map(self.width, self.height, self.seed);
+        self.changes.iter()
+            .map(|change| {
+                for (row, column, tile) in &change.tiles {
+                    mines[*row][*column] = tile.clone();
+                }
+                Step { guess: change.guess.clone(), mines: mines.clone() }
+            })
+            .collect()
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.started.map_or(Duration::ZERO, |started| started.elapsed())
+    }
+
+    pub fn hints(&self) -> usize {
+        self.hints
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        Difficulty::of_size(self.width, self.height)
+    }
+
+    /// The mines without a defuser on them, it goes below zero with too many defusers
+    pub fn mines_left(&self) -> isize {
+        let defusers: usize = self.mines.iter()
+            .map(|row| row.iter().filter(|tile| matches!(tile, TileState::Marked(_))).count())
+            .sum();
+        count_mines(&self.mines) as isize - defusers as isize
+    }
+
+    fn defused(&self) -> usize {
+        self.mines.iter()
+            .map(|row| row.iter().filter(|tile| matches!(tile, TileState::Marked(num) if *num < 0)).count())
+            .sum()
+    }
+
+    /// Saves the game in the stats, and in the history if it was played
+    fn save(&self, defused: usize, revealed: usize, record: Option<GameRecord>) {
+        let entry = record.map(|record| HistoryRecord::new(&record, self.seed, self.hints, defused, revealed));
+        report_saving(save_game(defused, revealed, entry.as_ref()));
+    }
+
+    /// Tells if the game counts as a no flag (NF) game
+    ///
+    /// Every game of the no flag mode counts, and a normal game if it was won without any flags.
+    fn no_flag(&self, rules: &GameRules, won: bool) -> bool {
+        rules.no_flag || (won && !self.flagged)
+    }
+
+    /// Shows the result of the game, and saves it in the stats
+    pub fn finish(&self, ending: Ending, rules: &GameRules) {
+        let (visible, _) = self.progress();
+        match ending {
+            Ending::Won => {
+                println!("{}", tr("Success! All mines defused!"));
+                visualize_map(&self.mines, 'X', false);
+                if rules.training {
+                    println!("{}\n", trf("Training game finished with {} rewinds.", &[&self.rewinds]));
+                    report_saving(save_training_stats(visible, self.rewinds, true));
+                    return;
+                }
+                let total = self.mines.len() * self.mines[0].len();
+                let mut record = GameRecord::new(&self.mines, GameResult::Won, self.clicks, self.duration());
+                record.no_flag = self.no_flag(rules, true);
+                if record.no_flag {
+                    println!("{}", tr("You won without using a single flag (NF)!"));
+                }
+                print_game_summary(&record);
+                self.save(total - visible, visible, Some(record));
+            },
+            Ending::Lost => {
+                println!("{}", tr("That was a mine. Game over."));
+                visualize_map(&self.mines, '*', true);
+                if !accessible() {
+                    for token in post_game_legend() {
+                        print!("{token}");
+                    }
+                    println!();
+                }
+                if let Some((row, column, fatal_move)) = &self.fatal_move {
+                    println!("{}", trf("The mine exploded at {}. {}", &[&coordinate_scheme().position_name(*row, *column), &fatal_move.describe()]));
+                }
+                let mut record = GameRecord::new(&self.mines, GameResult::Lost, self.clicks, self.duration());
+                record.no_flag = self.no_flag(rules, false);
+                print_game_summary(&record);
+                self.save(self.defused(), visible, Some(record));
+            },
+            // a restarted game is left unfinished, like a quit one
+            Ending::Quit | Ending::Restart => {
+                if rules.training {
+                    if self.started.is_some() {
+                        report_saving(save_training_stats(visible, self.rewinds, false));
+                    }
+                    return;
+                }
+                let record = self.started.map(|started| GameRecord {
+                    no_flag: self.no_flag(rules, false),
+                    ..GameRecord::new(&self.mines, GameResult::Abandoned, self.clicks, started.elapsed())
+                });
+                self.save(self.defused(), visible, record);
+            },
+        }
+    }
+}
+
+/// Tells if the stats could not be saved, the game goes on without them
+fn report_saving(saved: io::Result<()>) {
+    if let Err(err) = saved {
+        println!("{}", trf("Failed to save the stats. {}", &[&err]));
+    }
+}
+
+/// The position of the exploded tile, if there is one
+fn find_explosion(mine_map: &[Vec<TileState>]) -> Option<(usize, usize)> {
+    mine_map.iter().enumerate().find_map(|(row, line)| {
+        line.iter().position(|tile| *tile == TileState::Explosion).map(|column| (row, column))
+    })
+}
+
+#[test]
+fn steps_test() {
+    let mut game = Game::new(6, 5);
+    let rules = GameRules::default();
+    assert!(game.steps().is_empty());
+    game.play("A1", &rules);
+    game.play("hint", &rules);
+    game.play("def F5", &rules);
+
+    let steps = game.steps();
+    assert_eq!("A1", steps[0].guess);
+    assert_eq!(Some(&game.mines), steps.last().map(|step| &step.mines));
+    assert!(steps.iter().all(|step| step.mines != generate_map(6, 5, game.seed)));
+}
+
+#[test]
+fn no_flag_test() {
+    let mut game = Game::new(6, 5);
+    let mut rules = GameRules::default();
+    assert!(game.no_flag(&rules, true));
+    assert!(!game.no_flag(&rules, false));
+    game.flagged = true;
+    assert!(!game.no_flag(&rules, true));
+    rules.no_flag = true;
+    assert!(game.no_flag(&rules, true));
+    assert!(game.no_flag(&rules, false));
+}
+
+#[test]
+fn find_explosion_test() {
+    let mut test_map = vec![vec![TileState::HiddenEmpty(1), TileState::Mine]];
+    assert_eq!(None, find_explosion(&test_map));
+    test_map[0][1] = TileState::Explosion;
+    assert_eq!(Some((0, 1)), find_explosion(&test_map));
+}
+
+#[test]
+fn training_rewinds_test() {
+    let mut game = Game::new(2, 1);
+    game.mines = vec![vec![TileState::VisibleEmpty(1), TileState::Mine]];
+    game.first_guess = false;
+    let rules = GameRules { training: true, ..Default::default() };
+
+    capture_messages(true);
+    assert!(game.play("a2", &rules));
+    let messages = take_messages();
+    capture_messages(false);
+
+    assert_eq!(TileState::Mine, game.mines[0][1]);
+    assert_eq!(1, game.rewinds);
+    assert_eq!(1, game.clicks);
+    assert!(messages.contains(&"That tile was provably a mine, the numbers around it give it away.".to_string()));
+}
@@ -1,57 +1,238 @@
-use std::io::{self, stdout, ErrorKind};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, ErrorKind};
+use std::sync::{Mutex, OnceLock};
 use std::thread::sleep;
 use std::time::Duration;
 
-use crossterm::style::SetAttribute;
 use rand::Rng;
 use regex::Regex;
 
-use super::map_generator::TileState;
+use super::map_generator::{neighbours, TileState};
 use super::map_draw::*;
+use super::coords::{coordinate_scheme, coords_command};
+use super::locale::{local_words, tr, trf};
+use super::theme::{paint_text, slow_output, theme_command};
 
-use crossterm::{
-    style::{Color, Print, ResetColor, SetForegroundColor},
-    ExecutableCommand
-};
+use crossterm::style::{ContentStyle, Stylize};
 
 static QUIT_COMMANDS: [&str; 3] = ["q", "quit", "exit"];
 static CREDITS_COMMANDS: [&str; 2] = ["credits", "credit"];
 static ABOUT_COMMANDS: [&str; 1] = ["about"];
 static HELP_COMMANDS: [&str; 5] = ["help", "how", "how to", "?", "usage"];
+/// The built-in words an alias can take, other clones use `?` for the question marks
+static SPARE_WORDS: [&str; 1] = ["?"];
 static STAT_COMMANDS: [&str; 2] = ["stat", "stats"];
 static RESTART_COMMANDS: [&str; 1] = ["restart"];
 static HINT_COMMANDS: [&str; 1] = ["hint"];
+static REVEAL_COMMANDS: [&str; 1] = ["reveal"];
+static DEFUSE_COMMANDS: [&str; 1] = ["def"];
+static MARK_COMMANDS: [&str; 1] = ["mark"];
+static CHORD_COMMANDS: [&str; 1] = ["chord"];
+static TRAINING_COMMANDS: [&str; 3] = ["training", "train", "practice"];
+static NO_FLAG_COMMANDS: [&str; 3] = ["nf", "noflag", "no flag"];
 static MAP_SIZE: [&str; 4] = ["s", "m", "l", "xl"];
 static MAP_SIZE_SMALL: [&str; 2] = ["s", "small"];
 static MAP_SIZE_MED: [&str; 2] = ["m", "medium"];
 static MAP_SIZE_LARGE: [&str; 2] = ["l", "large"];
 static MAP_SIZE_EX: [&str; 4] = ["xl", "xxl", "extra", "extra large"];
+static SIZE_QUESTION: &str = "How big map would you like? s, m, l, xl";
+
+/// The commands which can have aliases
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Command {
+    Quit,
+    Credits,
+    About,
+    Help,
+    Stat,
+    Restart,
+    Hint,
+    Reveal,
+    Defuse,
+    Mark,
+    Chord,
+}
+
+static ALL_COMMANDS: [Command; 11] = [Command::Quit, Command::Credits, Command::About, Command::Help, Command::Stat,
+    Command::Restart, Command::Hint, Command::Reveal, Command::Defuse, Command::Mark, Command::Chord];
+
+impl Command {
+    /// The built-in words of the command
+    fn words(&self) -> &'static [&'static str] {
+        match self {
+            Command::Quit => &QUIT_COMMANDS,
+            Command::Credits => &CREDITS_COMMANDS,
+            Command::About => &ABOUT_COMMANDS,
+            Command::Help => &HELP_COMMANDS,
+            Command::Stat => &STAT_COMMANDS,
+            Command::Restart => &RESTART_COMMANDS,
+            Command::Hint => &HINT_COMMANDS,
+            Command::Reveal => &REVEAL_COMMANDS,
+            Command::Defuse => &DEFUSE_COMMANDS,
+            Command::Mark => &MARK_COMMANDS,
+            Command::Chord => &CHORD_COMMANDS,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Quit => "quit",
+            _ => self.words()[0],
+        }
+    }
+}
+
+/// The aliases of the player, set once from the config file
+static ALIASES: OnceLock<Vec<(String, Command)>> = OnceLock::new();
+
+fn aliases() -> &'static [(String, Command)] {
+    ALIASES.get().map_or(&[], |aliases| &aliases[..])
+}
+
+/// Checks the aliases from the config file, and activates the ones without problems
+///
+/// Gives back a warning for every alias which was skipped.
+pub fn set_aliases(config: &HashMap<String, String>) -> Vec<String> {
+    let (checked, warnings) = check_aliases(config);
+    let _ = ALIASES.set(checked);
+    warnings
+}
+
+fn check_aliases(config: &HashMap<String, String>) -> (Vec<(String, Command)>, Vec<String>) {
+    let move_regex = Regex::new("^[0-9]+[a-zA-Z]+$|^[a-zA-Z]+[0-9]+$").unwrap();
+    let mut checked: Vec<(String, Command)> = Vec::new();
+    let mut warnings = Vec::new();
+
+    // sorted, so the warnings are always the same
+    let mut entries: Vec<(&String, &String)> = config.iter().collect();
+    entries.sort();
+    for (alias, target) in entries {
+        let alias = alias.trim().to_lowercase();
+        let Some(command) = ALL_COMMANDS.iter().find(|command| is_one_of(target, command.words())) else {
+            warnings.push(trf("The alias '{}' is for '{}', which is not a command.", &[&alias, target]));
+            continue;
+        };
+        if alias.is_empty() || alias.contains(char::is_whitespace) {
+            warnings.push(trf("The alias '{}' can't be empty or contain spaces.", &[&alias]));
+        } else if let Some(taken) = ALL_COMMANDS.iter().find(|command| is_one_of(&alias, command.words()) && !SPARE_WORDS.contains(&&alias[..])) {
+            warnings.push(trf("The alias '{}' is already a command for '{}'.", &[&alias, &taken.name()]));
+        } else if [&MAP_SIZE_SMALL[..], &MAP_SIZE_MED, &MAP_SIZE_LARGE, &MAP_SIZE_EX, &TRAINING_COMMANDS, &NO_FLAG_COMMANDS]
+                .iter().any(|words| is_one_of(&alias, words)) {
+            warnings.push(trf("The alias '{}' is already used before choosing the map size.", &[&alias]));
+        } else if move_regex.is_match(&alias) {
+            warnings.push(trf("The alias '{}' looks like a position on the map.", &[&alias]));
+        } else if let Some((_, other)) = checked.iter().find(|(existing, _)| *existing == alias) {
+            warnings.push(trf("The alias '{}' is set for both '{}' and '{}'.", &[&alias, &other.name(), &command.name()]));
+        } else {
+            checked.push((alias, *command));
+        }
+    }
+    (checked, warnings)
+}
+
+/// Tells if the input is one of the words, in English or in the language of the game
+fn is_one_of(input: &str, words: &[&str]) -> bool {
+    let input = input.trim().to_lowercase();
+    words.contains(&&input[..]) || local_words(words[0]).contains(&&input[..])
+}
+
+/// Tells if the word is one of the built-in words or aliases of the command
+fn is_command(word: &str, command: Command) -> bool {
+    is_command_of(word, command, aliases())
+}
+
+/// An alias goes first, it can only take a built-in word from the spare ones
+fn is_command_of(word: &str, command: Command, aliases: &[(String, Command)]) -> bool {
+    let word = word.trim().to_lowercase();
+    match aliases.iter().find(|(alias, _)| *alias == word) {
+        Some((_, aliased)) => *aliased == command,
+        None => is_one_of(&word, command.words()),
+    }
+}
+
+/// Messages kept for the next frame, while the screen is redrawn in place
+static CAPTURED_MESSAGES: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+/// Tells something to the player about their move
+///
+/// It is printed right away, unless the messages are captured for a full screen frame.
+pub fn notify(message: impl Into<String>) {
+    let message = message.into();
+    match CAPTURED_MESSAGES.lock().unwrap().as_mut() {
+        Some(captured) => captured.push(message),
+        None => println!("{message}"),
+    }
+}
+
+/// Starts or stops keeping the messages instead of printing them
+pub fn capture_messages(capture: bool) {
+    *CAPTURED_MESSAGES.lock().unwrap() = if capture { Some(Vec::new()) } else { None };
+}
+
+/// Tells if the messages go to a full screen frame instead of the output
+fn messages_captured() -> bool {
+    CAPTURED_MESSAGES.lock().unwrap().is_some()
+}
+
+/// The messages kept since the last call
+pub fn take_messages() -> Vec<String> {
+    CAPTURED_MESSAGES.lock().unwrap().as_mut().map(std::mem::take).unwrap_or_default()
+}
 
 pub fn print_welcome() {
-    println!("Hello, minesweeper!\n");
+    println!("{}\n", tr("Hello, minesweeper!"));
 
-    println!("Your task is to defuse all the mines.");
+    println!("{}", tr("Your task is to defuse all the mines."));
     print_help();
 }
 
 pub fn print_error_with_help() {
-    println!("I don't understand this.\n");
+    println!("{}\n", tr("I don't understand this."));
     print_help()
 }
 
 pub fn print_help() {
-    println!("To reveal a tile, type the column and row - like \"A1\" or \"28BC\"");
-    println!("To mark as a potential mine, type \"mark\" with the position - like \"mark A1\" or \"mark 28BC\". It will be shown as a '?' (question mark)");
-    println!("To defuse a mine, type \"def\" with the position - like \"def A1\" or \"def 28BC\". It will be shown as a '.' (dot)\n");
-    println!("Type \"def\" with the position again to remove the defuser.\n");
-    println!("You can use some hints, type {} to reveal a random tile\n", join_tokens(HINT_COMMANDS));
-    println!("If you want to restart the game, type {}\n", join_tokens(RESTART_COMMANDS));
-    println!("If you want to close the game, type {}", join_tokens(QUIT_COMMANDS));
+    let scheme = coordinate_scheme();
+    let (first, other) = (scheme.position_name(0, 0), scheme.position_name(54, 27));
+    println!("{}", trf("To reveal a tile, type {} - like \"{}\" or \"{}\"", &[&tr(scheme.explanation()), &first, &other]));
+    println!("{}", trf("To mark as a potential mine, type {} with the position - like \"mark {}\" or \"mark {}\". It will be shown as a '?' (question mark)",
+        &[&command_tokens(&MARK_COMMANDS), &first, &other]));
+    println!("{}\n", trf("To defuse a mine, type {} with the position - like \"def {}\" or \"def {}\". It will be shown as a '.' (dot)",
+        &[&command_tokens(&DEFUSE_COMMANDS), &first, &other]));
+    println!("{}\n", tr("Type \"def\" with the position again to remove the defuser."));
+    println!("{}\n", trf("To reveal the neighbours of a number which has all its mines defused, type {} with the position - like \"chord {}\"",
+        &[&command_tokens(&CHORD_COMMANDS), &first]));
+    println!("{}\n", trf("You can use some hints, type {} to reveal a random tile", &[&command_tokens(&HINT_COMMANDS)]));
+    println!("{}\n", trf("If you want to restart the game, type {}", &[&command_tokens(&RESTART_COMMANDS)]));
+    println!("{}\n", tr("To change the colors, type 'theme' with the name of a theme - like \"theme deuteranopia-safe\""));
+    println!("{}\n", tr("To name the tiles in another way, type 'coords' with 'letters', 'spreadsheet' or 'numeric'"));
+    println!("{}\n", tr("To draw the tiles smaller or bigger, type 'zoom' with 'compact', 'normal' or 'large', or 'zoom in' and 'zoom out'"));
+    println!("{}", tr("To save the map as an image, type 'export svg' with the name of the file - like \"export svg map.svg\""));
+    println!("{}\n", tr("For a web page type 'export html', or 'export html steps' to step through the moves - like \"export html steps game.html\""));
+    println!("{}\n", trf("To hear about the map, type \"describe\" with a position, \"row\" with a row name or \"frontier\" - like \"describe {}\"", &[&first]));
+    println!("{}", trf("If you want to close the game, type {}", &[&command_tokens(&QUIT_COMMANDS)]));
+    print_aliases();
+}
+
+fn print_aliases() {
+    if aliases().is_empty() {
+        return;
+    }
+    println!("\n{}", tr("Your aliases:"));
+    for command in ALL_COMMANDS {
+        let words: Vec<&str> = aliases().iter()
+            .filter(|(_, aliased)| *aliased == command)
+            .map(|(alias, _)| &alias[..])
+            .collect();
+        if !words.is_empty() {
+            println!("    {}", trf("{} for '{}'", &[&join_tokens(&words), &command.name()]));
+        }
+    }
 }
 
 pub fn print_about() {
     // here comes the open source crates I used
-    println!(r#"Open source projects and their license used in this game:"#);
+    println!("{}", tr("Open source projects and their license used in this game:"));
     // rand = "0.8.5"
     println!("    rand");
     println!(r#"Copyright 2018 Developers of the Rand project
@@ -213,88 +394,158 @@ SOFTWARE."#);
 }
 
 pub fn print_credits() {
-    println!("");
-    stdout()
-        .execute(SetForegroundColor(Color::DarkYellow)).unwrap()
-        .execute(Print("   Chromatic Carrot\n")).unwrap()
-        .execute(ResetColor).unwrap();
-    stdout()
-        .execute(SetForegroundColor(Color::Blue)).unwrap()
-        .execute(SetAttribute(crossterm::style::Attribute::Underlined)).unwrap()
-        .execute(Print("www.chromaticcarrot.com\n")).unwrap()
-        .execute(ResetColor).unwrap();
-    println!("  Graphics designer:");
-    println!("(nobody)");
-    println!("  Programmer:");
+    println!();
+    println!("{}", paint_text("   Chromatic Carrot", ContentStyle::new().dark_yellow()));
+    println!("{}", paint_text("www.chromaticcarrot.com", ContentStyle::new().blue().underlined()));
+    println!("  {}", tr("Graphics designer:"));
+    println!("{}", tr("(nobody)"));
+    println!("  {}", tr("Programmer:"));
     println!("Balazs Erseki ~ zerocukor\n");
-    sleep(Duration::from_secs(2));
+    if slow_output() {
+        sleep(Duration::from_secs(2));
+    }
 }
 
 pub fn want_to_quit(input: &str) -> bool {
-    QUIT_COMMANDS.contains(&&input.trim().to_lowercase()[..])
+    is_command(input, Command::Quit)
 }
 
 pub fn help(input: &str) -> bool {
-    HELP_COMMANDS.contains(&&input.trim().to_lowercase()[..])
+    is_command(input, Command::Help)
 }
 pub fn stat(input: &str) -> bool {
-    STAT_COMMANDS.contains(&&input.trim().to_lowercase()[..])
+    is_command(input, Command::Stat)
 }
 
 pub fn map_small(input: &str) -> bool {
-    MAP_SIZE_SMALL.contains(&&input.trim().to_lowercase()[..])
+    is_one_of(input, &MAP_SIZE_SMALL)
 }
 
 pub fn map_medium(input: &str) -> bool {
-    MAP_SIZE_MED.contains(&&input.trim().to_lowercase()[..])
+    is_one_of(input, &MAP_SIZE_MED)
 }
 
 pub fn map_large(input: &str) -> bool {
-    MAP_SIZE_LARGE.contains(&&input.trim().to_lowercase()[..])
+    is_one_of(input, &MAP_SIZE_LARGE)
 }
 
 pub fn map_extra(input: &str) -> bool {
-    MAP_SIZE_EX.contains(&&input.trim().to_lowercase()[..])
+    is_one_of(input, &MAP_SIZE_EX)
 }
 
 pub fn credits(input: &str) -> bool {
-    CREDITS_COMMANDS.contains(&&input.trim().to_lowercase()[..])
+    is_command(input, Command::Credits)
 }
 
 pub fn about(input: &str) -> bool {
-    ABOUT_COMMANDS.contains(&&input.trim().to_lowercase()[..])
+    is_command(input, Command::About)
 }
 
 pub fn restart(input: &str) -> bool {
-    RESTART_COMMANDS.contains(&&input.trim().to_lowercase()[..])
+    is_command(input, Command::Restart)
 }
 
-fn join_tokens<const L: usize>(array: [&str; L]) -> String {
+fn join_tokens<S: AsRef<str>>(array: &[S]) -> String {
     let mut ret = String::new();
     for (i, element) in array.iter().enumerate() {
         if i > 0 {
-            if i == L-1 {
+            if i == array.len()-1 {
                 // last element
-                ret.push_str(", or ")
+                ret.push_str(tr(", or "))
             } else {
                 ret.push_str(", ")
             }
         }
-        ret.push_str(&format!("'{}'", element));
+        ret.push_str(&format!("'{}'", element.as_ref()));
     }
     ret
 }
 
+/// The words of a command for the player, the translated ones too
+fn command_tokens(words: &[&str]) -> String {
+    let all: Vec<&str> = words.iter().chain(local_words(words[0])).copied().collect();
+    join_tokens(&all)
+}
+
 #[test]
 fn join_tokens_test() {
-    assert_eq!(join_tokens([]), "");
-    assert_eq!(join_tokens(["General"]), "'General'");
-    assert_eq!(join_tokens(["General", "Kenobi"]), "'General', or 'Kenobi'");
-    assert_eq!(join_tokens(QUIT_COMMANDS), "'q', 'quit', or 'exit'");
+    assert_eq!(join_tokens::<&str>(&[]), "");
+    assert_eq!(join_tokens(&["General"]), "'General'");
+    assert_eq!(join_tokens(&["General", "Kenobi"]), "'General', or 'Kenobi'");
+    assert_eq!(join_tokens(&QUIT_COMMANDS), "'q', 'quit', or 'exit'");
+}
+
+/// The map sizes of the menu, every other size is custom
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    Small,
+    Medium,
+    Large,
+    ExtraLarge,
+    Custom,
 }
 
-pub fn get_size() -> (u8, u8) {
-    println!("How big map would you like? s, m, l, xl");
+static PRESET_SIZES: [(Difficulty, (u8, u8)); 4] = [
+    (Difficulty::Small, (6, 5)),
+    (Difficulty::Medium, (10, 8)),
+    (Difficulty::Large, (15, 13)),
+    (Difficulty::ExtraLarge, (35, 30)),
+];
+
+impl Difficulty {
+    pub fn of_size(width: u8, height: u8) -> Difficulty {
+        PRESET_SIZES.iter()
+            .find(|(_, size)| *size == (width, height))
+            .map_or(Difficulty::Custom, |(difficulty, _)| *difficulty)
+    }
+
+    /// The smallest preset with at least this many tiles, to group the custom sizes
+    pub fn by_tiles(tiles: usize) -> Difficulty {
+        PRESET_SIZES.iter()
+            .find(|(_, (width, height))| usize::from(*width) * usize::from(*height) >= tiles)
+            .map_or(Difficulty::ExtraLarge, |(difficulty, _)| *difficulty)
+    }
+
+    /// Width and height of a preset, (0, 0) for the custom ones
+    pub fn size(&self) -> (u8, u8) {
+        PRESET_SIZES.iter()
+            .find(|(difficulty, _)| difficulty == self)
+            .map_or((0, 0), |(_, size)| *size)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Small => "small",
+            Difficulty::Medium => "medium",
+            Difficulty::Large => "large",
+            Difficulty::ExtraLarge => "extra large",
+            Difficulty::Custom => "custom",
+        }
+    }
+}
+
+/// Special rules for the next game, set before choosing the map size
+#[derive(Default)]
+pub struct GameRules {
+    /// Hitting a mine rewinds the move instead of ending the game
+    pub training: bool,
+    /// Defusers and question marks are not allowed
+    pub no_flag: bool,
+}
+
+pub fn training(input: &str) -> bool {
+    is_one_of(input, &TRAINING_COMMANDS)
+}
+
+pub fn no_flag(input: &str) -> bool {
+    is_one_of(input, &NO_FLAG_COMMANDS)
+}
+
+pub fn get_size(rules: &mut GameRules) -> (u8, u8) {
+    println!("{}", tr(SIZE_QUESTION));
+    println!("{}", trf("Type {} to switch training mode, or {} to play without flags",
+        &[&command_tokens(&TRAINING_COMMANDS), &command_tokens(&NO_FLAG_COMMANDS)]));
     loop {
         let mut input = String::new();
         io::stdin().read_line(&mut input)
@@ -305,25 +556,43 @@ pub fn get_size() -> (u8, u8) {
             return (0, 0);
         } else if help(&input) {
             print_help();
-            println!("How big map would you like? s, m, l, xl");
+            println!("{}", tr(SIZE_QUESTION));
         } else if credits(&input) {
             print_credits();
-            println!("How big map would you like? s, m, l, xl");
+            println!("{}", tr(SIZE_QUESTION));
         } else if about(&input) {
             print_about();
             print_credits();
-            println!("How big map would you like? s, m, l, xl");
+            println!("{}", tr(SIZE_QUESTION));
+        } else if theme_command(&input) || coords_command(&input) {
+            println!("{}", tr(SIZE_QUESTION));
+        } else if training(&input) {
+            rules.training = !rules.training;
+            if rules.training {
+                println!("{}", tr("Training mode is on, hitting a mine rewinds the move. These games are not counted in your stats."));
+            } else {
+                println!("{}", tr("Training mode is off."));
+            }
+            println!("{}", tr(SIZE_QUESTION));
+        } else if no_flag(&input) {
+            rules.no_flag = !rules.no_flag;
+            if rules.no_flag {
+                println!("{}", tr("No flag mode is on, you can't use 'def' and 'mark' in the next games."));
+            } else {
+                println!("{}", tr("No flag mode is off."));
+            }
+            println!("{}", tr(SIZE_QUESTION));
         } else if map_small(&input) {
-            return (6, 5)
+            return Difficulty::Small.size();
         } else if map_medium(&input) {
-            return (10, 8);
+            return Difficulty::Medium.size();
         } else if map_large(&input) {
-            return (15, 13);
+            return Difficulty::Large.size();
         } else if map_extra(&input) {
-            return (35, 30);
+            return Difficulty::ExtraLarge.size();
         } else {
-            println!("I don't understand this: {}. Type {} to set map size or {} to quit",
-                input, join_tokens(MAP_SIZE), join_tokens(QUIT_COMMANDS));
+            println!("{}", trf("I don't understand this: {}. Type {} to set map size or {} to quit",
+                &[&input, &join_tokens(&MAP_SIZE), &command_tokens(&QUIT_COMMANDS)]));
         }
     }
 }
@@ -334,15 +603,13 @@ static POSSIBLE_INPUTS_YES: [&str; 3] = ["y", "yes", "yeah"];
 /// This will keep asking the player if they want to start again, or exit
 pub fn start_again() -> bool {
     let mut input = String::new();
-    while !want_to_quit(&input) &&
-            !POSSIBLE_INPUTS_NO.contains(&input.trim().to_lowercase().as_str()) &&
-            !POSSIBLE_INPUTS_YES.contains(&input.trim().to_lowercase().as_str()) {
+    while !want_to_quit(&input) && !is_one_of(&input, &POSSIBLE_INPUTS_NO) && !is_one_of(&input, &POSSIBLE_INPUTS_YES) {
         input.clear();
-        println!("Do you want to start again? (y/n)");
+        println!("{}", tr("Do you want to start again? (y/n)"));
         io::stdin().read_line(&mut input)
             .expect("Failed to read.");
     }
-    POSSIBLE_INPUTS_YES.contains(&input.trim().to_lowercase().as_str())
+    is_one_of(&input, &POSSIBLE_INPUTS_YES)
 }
 
 pub enum MoveResult {
@@ -352,62 +619,84 @@ pub enum MoveResult {
     AlreadyRevealed,
 }
 
-/// It parses the move of the player, applies to the map, and returns if the player exploded or not
-pub fn process_input(guess: &str, mines: &mut Vec<Vec<TileState>>) -> bool{
-    match translate_move(&guess) {
-        MoveType::Unknown =>print_error_with_help(),
+/// What a move did to the map, so it can be shown
+#[derive(Clone, Debug)]
+pub struct MoveReport {
+    /// `false` if a mine exploded
+    pub safe: bool,
+    /// The tile the move was made on, or the one revealed by a hint
+    pub tile: Option<(usize, usize)>,
+    /// The tiles revealed by the move
+    pub opened: HashSet<(usize, usize)>,
+}
+
+/// It applies the parsed move of the player to the map, and returns if the player exploded or not, with the changed tiles
+pub fn process_input(guess: &str, move_type: MoveType, mines: &mut Vec<Vec<TileState>>, rules: &GameRules) -> MoveReport {
+    let mut report = MoveReport { safe: true, tile: None, opened: HashSet::new() };
+    if rules.no_flag && move_type.uses_flag() {
+        notify(tr("Flags are off in no flag mode, you can only reveal tiles."));
+        return report;
+    }
+    let (result, problem) = match move_type {
+        MoveType::Unknown => {
+            notify(explain_unknown_move(guess, mines.len(), mines[0].len()));
+            return report;
+        },
         MoveType::Reveal { row, column } => {
-            if row as usize >= mines.len() || column as usize >= mines[0].len() {
-                println!("That tile is not existing."); return true;
-            }
-            match reveal_tile(row as usize, column as usize, mines, false) {
-                MoveResult::Explosion => {
-                    return false;
-                },
-                MoveResult::SafeMove => (),
-                MoveResult::AlreadyRevealed => println!("Already revealed..."),
-                MoveResult::MakesNoSense => {
-                    print_error_with_help();
-                },
-            }
+            let Some((row, column)) = tile_on_map(row, column, mines) else {
+                return report;
+            };
+            let (result, opened) = reveal_tile(row, column, mines, false);
+            report = MoveReport { tile: Some((row, column)), opened, ..report };
+            (result, None)
         },
         MoveType::Defuse { row, column } => {
-            if row as usize >= mines.len() || column as usize >= mines[0].len() {
-                println!("That tile is not existing.");
-                return true;
-            }
-            match defuse_tile(row as usize, column as usize, mines) {
-                MoveResult::Explosion => {
-                    return false;
-                },
-                MoveResult::SafeMove => (),
-                MoveResult::AlreadyRevealed => println!("Already revealed..."),
-                MoveResult::MakesNoSense => {
-                    println!("Type 'def' with position to remove the defuser.");
-                },
-            }
+            let Some((row, column)) = tile_on_map(row, column, mines) else {
+                return report;
+            };
+            report.tile = Some((row, column));
+            (defuse_tile(row, column, mines), Some("Type 'def' with position to remove the defuser."))
         },
         MoveType::Mark { row, column } => {
-            if row as usize >= mines.len() || column as usize >= mines[0].len() {
-                println!("That tile is not existing.");
-                return true;
-            }
-            match mark_tile(row as usize, column as usize, mines) {
-                MoveResult::Explosion => {
-                    return false;
-                },
-                MoveResult::SafeMove => (),
-                MoveResult::AlreadyRevealed => println!("Already revealed..."),
-                MoveResult::MakesNoSense => {
-                    println!("Type 'def' with position to remove the defuser.");
-                },
-            }
+            let Some((row, column)) = tile_on_map(row, column, mines) else {
+                return report;
+            };
+            report.tile = Some((row, column));
+            (mark_tile(row, column, mines), Some("Type 'def' with position to remove the defuser."))
         },
-        MoveType::Hint => {
-            show_hint(mines);
-        }
+        MoveType::Chord { row, column } => {
+            let Some((row, column)) = tile_on_map(row, column, mines) else {
+                return report;
+            };
+            let (result, opened) = chord_tile(row, column, mines);
+            report = MoveReport { tile: Some((row, column)), opened, ..report };
+            (result, Some("Only a revealed number with the same number of defusers around it can be chorded."))
+        },
+        MoveType::Hint => return show_hint(mines),
     };
-    true
+    match result {
+        MoveResult::Explosion => report.safe = false,
+        MoveResult::SafeMove => (),
+        MoveResult::AlreadyRevealed => notify(tr("Already revealed...")),
+        MoveResult::MakesNoSense => match problem {
+            Some(problem) => notify(tr(problem)),
+            // the full screen has no room for the help
+            None if messages_captured() => notify(tr("That tile is defused, type 'def' with the position to remove the defuser first.")),
+            None => print_error_with_help(),
+        },
+    }
+    report
+}
+
+/// The tile of a move, or `None` if it is outside of the map, the player is told why
+fn tile_on_map(row: u8, column: u8, mines: &[Vec<TileState>]) -> Option<(usize, usize)> {
+    match outside_of_map(row, column, mines.len(), mines[0].len()) {
+        Some(problem) => {
+            notify(problem);
+            None
+        },
+        None => Some((row as usize, column as usize)),
+    }
 }
 
 fn best_hint(mines: &Vec<Vec<TileState>>) -> i16 {
@@ -415,9 +704,9 @@ fn best_hint(mines: &Vec<Vec<TileState>>) -> i16 {
     for line in mines {
         for tile in line {
             match tile {
-                TileState::Marked(num) => if *num > 0 && *num < best_hidden {best_hidden = *num;},
-                TileState::HiddenEmpty(num ) => if i16::from(*num) < best_hidden {best_hidden = (*num) as i16;},
-                TileState::Question(num) => if *num > 0 && *num < best_hidden {best_hidden = *num;},
+                TileState::Marked(num) if *num > 0 && *num < best_hidden => best_hidden = *num,
+                TileState::HiddenEmpty(num) if i16::from(*num) < best_hidden => best_hidden = i16::from(*num),
+                TileState::Question(num) if *num > 0 && *num < best_hidden => best_hidden = *num,
                 _ => {},
             }
         }
@@ -425,22 +714,34 @@ fn best_hint(mines: &Vec<Vec<TileState>>) -> i16 {
     best_hidden
 }
 
-pub fn show_hint( mines: &mut Vec<Vec<TileState>>) -> MoveResult {
+/// Reveals a random safe tile, one of those with the fewest mines around
+pub fn show_hint(mines: &mut Vec<Vec<TileState>>) -> MoveReport {
     let best_hidden = best_hint(mines);
 
     let rand_column = rand::thread_rng().gen_range(0..=mines[0].len()-1);
     let rand_row = rand::thread_rng().gen_range(0..=mines.len()-1);
+    let reveal = |mines: &mut Vec<Vec<TileState>>| {
+        let (result, opened) = reveal_tile(rand_row, rand_column, mines, true);
+        MoveReport { safe: !matches!(result, MoveResult::Explosion), tile: Some((rand_row, rand_column)), opened }
+    };
     match mines[rand_row][rand_column] {
         TileState::Mine => show_hint(mines),
-        TileState::Explosion => MoveResult::Explosion,
-        TileState::Marked(num) => if num < 0 || num != best_hidden {show_hint(mines)} else {defuse_tile(rand_row, rand_column, mines);reveal_tile(rand_row, rand_column, mines, true)},
-        TileState::HiddenEmpty(num) => if i16::from(num) == best_hidden { reveal_tile(rand_row, rand_column, mines, true) } else { show_hint(mines) },
+        TileState::Explosion => MoveReport { safe: false, tile: None, opened: HashSet::new() },
+        TileState::Marked(num) => if num < 0 || num != best_hidden {show_hint(mines)} else {defuse_tile(rand_row, rand_column, mines);reveal(mines)},
+        TileState::HiddenEmpty(num) => if i16::from(num) == best_hidden { reveal(mines) } else { show_hint(mines) },
         TileState::VisibleEmpty(_) => show_hint(mines),
-        TileState::Question(num) => if num < 0 || num != best_hidden {show_hint(mines)} else {reveal_tile(rand_row, rand_column, mines, true)},
+        TileState::Question(num) => if num < 0 || num != best_hidden {show_hint(mines)} else {reveal(mines)},
     }
 }
 
-pub fn reveal_tile(row: usize, column: usize, mine_map: &mut Vec<Vec<TileState>>, force: bool) -> MoveResult {
+/// Reveals a tile, and the area around it if it is a zero, it gives back the tiles that changed too
+pub fn reveal_tile(row: usize, column: usize, mine_map: &mut Vec<Vec<TileState>>, force: bool) -> (MoveResult, HashSet<(usize, usize)>) {
+    let mut opened = HashSet::new();
+    let result = reveal_into(row, column, mine_map, force, &mut opened);
+    (result, opened)
+}
+
+fn reveal_into(row: usize, column: usize, mine_map: &mut Vec<Vec<TileState>>, force: bool, opened: &mut HashSet<(usize, usize)>) -> MoveResult {
     mine_map[row][column] = match mine_map[row][column]{
         TileState::Mine => TileState::Explosion,
         TileState::Explosion => TileState::Explosion,
@@ -454,6 +755,7 @@ pub fn reveal_tile(row: usize, column: usize, mine_map: &mut Vec<Vec<TileState>>
         TileState::VisibleEmpty(_) => return MoveResult::AlreadyRevealed,
         TileState::Question(x) => if x < 0 { return MoveResult::Explosion } else { TileState::VisibleEmpty(x as u8) },
     };
+    opened.insert((row, column));
 
     if mine_map[row][column] == TileState::Explosion {
         return MoveResult::Explosion;
@@ -461,36 +763,42 @@ pub fn reveal_tile(row: usize, column: usize, mine_map: &mut Vec<Vec<TileState>>
 
     // reveal neighbors
     if mine_map[row][column] == TileState::VisibleEmpty(0) {
-        if row > 0 {
-            if column > 0 {
-                reveal_tile(row-1, column-1, mine_map, true);
-            }
-            reveal_tile(row-1, column, mine_map, true);
-            if column + 1 < mine_map[0].len() {
-                reveal_tile(row-1, column+1, mine_map, true);
-            }
-        }
-        if column > 0 {
-            reveal_tile(row, column-1, mine_map, true);
-        }
-        if column + 1 < mine_map[0].len() {
-            reveal_tile(row, column+1, mine_map, true);
-        }
-        if row + 1 < mine_map.len() {
-            if column > 0 {
-                reveal_tile(row+1, column-1, mine_map, true);
-            }
-            reveal_tile(row+1, column, mine_map, true);
-            if column + 1 < mine_map[0].len() {
-                reveal_tile(row+1, column+1, mine_map, true);
-            }
+        for (neighbour_row, neighbour_column) in neighbours(row, column, mine_map.len(), mine_map[0].len()) {
+            reveal_into(neighbour_row, neighbour_column, mine_map, true, opened);
         }
     }
     // still any move left
     MoveResult::SafeMove
 }
 
-pub fn defuse_tile(row: usize, column: usize, mine_map: &mut Vec<Vec<TileState>>) -> MoveResult {
+/// Reveals the hidden neighbours of a number, if it has exactly that many defusers around it
+pub fn chord_tile(row: usize, column: usize, mine_map: &mut Vec<Vec<TileState>>) -> (MoveResult, HashSet<(usize, usize)>) {
+    let mut opened = HashSet::new();
+    let TileState::VisibleEmpty(number) = mine_map[row][column] else {
+        let result = if matches!(mine_map[row][column], TileState::Explosion) { MoveResult::AlreadyRevealed } else { MoveResult::MakesNoSense };
+        return (result, opened);
+    };
+    let around = neighbours(row, column, mine_map.len(), mine_map[0].len());
+    let defused = around.iter()
+        .filter(|(neighbour_row, neighbour_column)| matches!(mine_map[*neighbour_row][*neighbour_column], TileState::Marked(_)))
+        .count();
+    if defused != usize::from(number) {
+        return (MoveResult::MakesNoSense, opened);
+    }
+
+    let mut result = MoveResult::SafeMove;
+    for (neighbour_row, neighbour_column) in around {
+        if matches!(mine_map[neighbour_row][neighbour_column], TileState::Marked(_) | TileState::VisibleEmpty(_)) {
+            continue;
+        }
+        if let MoveResult::Explosion = reveal_into(neighbour_row, neighbour_column, mine_map, false, &mut opened) {
+            result = MoveResult::Explosion;
+        }
+    }
+    (result, opened)
+}
+
+pub fn defuse_tile(row: usize, column: usize, mine_map: &mut [Vec<TileState>]) -> MoveResult {
     mine_map[row][column] = match mine_map[row][column]{
         TileState::Mine => TileState::Marked(-1),
         TileState::Explosion => TileState::Explosion,
@@ -503,7 +811,7 @@ pub fn defuse_tile(row: usize, column: usize, mine_map: &mut Vec<Vec<TileState>>
     MoveResult::SafeMove
 }
 
-pub fn mark_tile(row: usize, column: usize, mine_map: &mut Vec<Vec<TileState>>) -> MoveResult {
+pub fn mark_tile(row: usize, column: usize, mine_map: &mut [Vec<TileState>]) -> MoveResult {
     mine_map[row][column] = match mine_map[row][column]{
         TileState::Mine => TileState::Question(-1),
         TileState::Explosion => TileState::Explosion,
@@ -516,33 +824,46 @@ pub fn mark_tile(row: usize, column: usize, mine_map: &mut Vec<Vec<TileState>>)
     MoveResult::SafeMove
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum MoveType {
     Unknown,
     Hint,
     Reveal{row: u8, column: u8},
     Defuse{row: u8, column: u8},
-    Mark{row: u8, column: u8}
+    Mark{row: u8, column: u8},
+    Chord{row: u8, column: u8},
 }
 
 pub fn translate_move(input: &str) -> MoveType {
-    let move_regex = Regex::new("^[0-9]+[a-zA-Z]+$|^[a-zA-Z]+[0-9]+$").unwrap();
-    if input.starts_with("def ") {
-        let index = parse_index(&input.trim()[4..]);
+    let (word, position) = input.trim().split_once(char::is_whitespace).unwrap_or((input.trim(), ""));
+    if is_command(word, Command::Defuse) {
+        let index = parse_index(position.trim());
         match index {
             Ok((row, column)) => MoveType::Defuse { row, column },
             Err(_) => MoveType::Unknown,
         }
-    } else if input.starts_with("mark ") {
-        let index = parse_index(&input.trim()[5..]);
+    } else if is_command(word, Command::Mark) {
+        let index = parse_index(position.trim());
         match index {
             Ok((row, column)) => MoveType::Mark { row, column },
             Err(_) => MoveType::Unknown,
         }
-    } else if HINT_COMMANDS.contains(&&input.trim().to_lowercase()[..]) {
+    } else if is_command(word, Command::Chord) {
+        let index = parse_index(position.trim());
+        match index {
+            Ok((row, column)) => MoveType::Chord { row, column },
+            Err(_) => MoveType::Unknown,
+        }
+    } else if is_command(word, Command::Reveal) && !position.is_empty() {
+        let index = parse_index(position.trim());
+        match index {
+            Ok((row, column)) => MoveType::Reveal { row, column },
+            Err(_) => MoveType::Unknown,
+        }
+    } else if is_command(input, Command::Hint) {
         MoveType::Hint
-    } else if move_regex.is_match(input.trim()) {
-        let index = parse_index(&input);
+    } else if coordinate_scheme().looks_like_position(input) {
+        let index = parse_index(input);
         match index {
             Ok((row, column)) => MoveType::Reveal { row, column },
             Err(_) => MoveType::Unknown,
@@ -552,14 +873,180 @@ pub fn translate_move(input: &str) -> MoveType {
     }
 }
 
-fn parse_index(input: &str) -> Result<(u8, u8), ErrorKind> {
-    let row = get_row_number(&input.chars().filter(|c| c.is_alphabetic()).collect::<String>());
-    let column = get_column_number(&input.chars().filter(|c| c.is_numeric()).collect::<String>());
+impl MoveType {
+    /// Tells if it is a move on the board, these are counted as clicks for the efficiency
+    pub fn is_click(&self) -> bool {
+        matches!(self, MoveType::Reveal { .. } | MoveType::Defuse { .. } | MoveType::Mark { .. } | MoveType::Chord { .. })
+    }
+
+    /// Tells if it places or removes a defuser or a question mark
+    pub fn uses_flag(&self) -> bool {
+        matches!(self, MoveType::Defuse { .. } | MoveType::Mark { .. })
+    }
+
+    pub fn is_hint(&self) -> bool {
+        matches!(self, MoveType::Hint)
+    }
+}
+
+/// Reads a position in the coordinates of the game, as row and column
+pub fn parse_index(input: &str) -> Result<(u8, u8), ErrorKind> {
+    coordinate_scheme().parse(input).map_err(|_| ErrorKind::InvalidInput)
+}
+
+/// Explains what is wrong with a move which couldn't be understood, and suggests the closest valid one
+pub fn explain_unknown_move(input: &str, height: usize, width: usize) -> String {
+    let input = input.trim();
+    if input.is_empty() {
+        return trf("Type a position like '{}', or 'help' to see the commands.", &[&position_name(0, 0)]);
+    }
+    let position_commands = [Command::Reveal, Command::Defuse, Command::Mark, Command::Chord];
 
-    match (row, column) {
-        (Ok(row), Ok(column)) => Ok((row, column)),
-        _ => Err(ErrorKind::InvalidInput)
+    if coordinate_scheme().looks_like_position(input) {
+        if let Some(problem) = explain_position(input, height, width) {
+            return capitalize(&problem);
+        }
     }
+    if let Some((word, position)) = input.split_once(char::is_whitespace) {
+        let position = position.trim();
+        if position_commands.iter().any(|command| is_command(word, *command)) {
+            return explain_position(position, height, width)
+                .map(|problem| capitalize(&problem))
+                .unwrap_or_else(|| trf("I don't understand '{}'.", &[&input]));
+        }
+        if let Some(closest) = closest_word(word, &position_commands) {
+            return match explain_position(position, height, width) {
+                Some(problem) => trf("Did you mean '{} {}'? Also {}", &[&closest, &position, &problem]),
+                None => trf("Did you mean '{} {}'?", &[&closest, &position]),
+            };
+        }
+    } else {
+        if let Some(command) = position_commands.iter().find(|command| is_command(input, **command)) {
+            return trf("'{}' needs a position too, like '{} {}'.", &[&input, &command.name(), &position_name(0, 0)]);
+        }
+        if input.chars().any(|ch| ch.is_ascii_digit()) {
+            if let Some(problem) = explain_position(input, height, width) {
+                return capitalize(&problem);
+            }
+        }
+    }
+
+    if let Some(closest) = closest_word(input, &ALL_COMMANDS) {
+        return trf("Did you mean '{}'?", &[&closest]);
+    }
+    trf("I don't understand '{}'. Type {} to see the commands.", &[&input, &join_tokens(&HELP_COMMANDS[..1])])
+}
+
+/// Tells what is wrong with a position, or `None` if it is on the map
+fn explain_position(position: &str, height: usize, width: usize) -> Option<String> {
+    let scheme = coordinate_scheme();
+    let Some(typed) = scheme.read_loosely(position) else {
+        return Some(trf("'{}' is not a position, type {} - like '{}'.", &[&position, &tr(scheme.explanation()), &scheme.position_name(0, 0)]));
+    };
+
+    if typed.column == 0 {
+        Some(tr("the columns start from 1.").to_string())
+    } else if typed.row == 0 {
+        Some(tr("the rows start from 1.").to_string())
+    } else if typed.column > width {
+        Some(trf("column {} is outside this {}-wide board.", &[&typed.column_text, &width]))
+    } else if typed.row > height {
+        Some(trf("row {} is outside this {}-tall board.", &[&typed.row_text, &height]))
+    } else {
+        None
+    }
+}
+
+fn capitalize(sentence: &str) -> String {
+    let mut chars = sentence.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// The message for a parsed position which is not on the map
+fn outside_of_map(row: u8, column: u8, height: usize, width: usize) -> Option<String> {
+    if column as usize >= width {
+        Some(trf("Column {} is outside this {}-wide board.", &[&coordinate_scheme().column_name(column as usize), &width]))
+    } else if row as usize >= height {
+        Some(trf("Row {} is outside this {}-tall board.", &[&coordinate_scheme().row_name(row as usize), &height]))
+    } else {
+        None
+    }
+}
+
+/// The command word closest to the typo, if there is any close enough
+fn closest_word(word: &str, commands: &[Command]) -> Option<String> {
+    let word = word.trim().to_lowercase();
+    let mut candidates: Vec<(&str, Command)> = Vec::new();
+    for command in commands {
+        candidates.extend(command.words().iter().chain(local_words(command.words()[0])).map(|builtin| (*builtin, *command)));
+    }
+    candidates.extend(aliases().iter()
+        .filter(|(_, command)| commands.contains(command))
+        .map(|(alias, command)| (&alias[..], *command)));
+
+    candidates.iter()
+        .map(|(candidate, _)| (edit_distance(&word, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= 2 && *distance < word.chars().count())
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+/// Number of single character edits between two words, swapping two neighbours counts as one
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            distances[i][j] = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distances[i][j] = distances[i][j].min(distances[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+#[test]
+fn edit_distance_test() {
+    assert_eq!(0, edit_distance("def", "def"));
+    assert_eq!(1, edit_distance("dfe", "def"));
+    assert_eq!(1, edit_distance("mak", "mark"));
+    assert_eq!(3, edit_distance("", "def"));
+    assert_eq!(3, edit_distance("hint", "quit"));
+}
+
+#[test]
+fn explain_unknown_move_test() {
+    assert_eq!("Did you mean 'def a1'?", explain_unknown_move("dfe a1", 13, 15));
+    assert_eq!("Did you mean 'mark c4'?", explain_unknown_move("makr c4\n", 13, 15));
+    assert_eq!("Did you mean 'hint'?", explain_unknown_move("hitn", 13, 15));
+    assert_eq!("Column 40 is outside this 15-wide board.", explain_unknown_move("def a40", 13, 15));
+    assert_eq!("Column 300 is outside this 15-wide board.", explain_unknown_move("a300", 13, 15));
+    assert_eq!("Row ZZ is outside this 13-tall board.", explain_unknown_move("zz3", 13, 15));
+    assert_eq!("The columns start from 1.", explain_unknown_move("c0", 13, 15));
+    assert_eq!("'def' needs a position too, like 'def A1'.", explain_unknown_move("def", 13, 15));
+    assert_eq!("Did you mean 'def a40'? Also column 40 is outside this 15-wide board.", explain_unknown_move("dfe a40", 13, 15));
+    assert_eq!("I don't understand 'banana'. Type 'help' to see the commands.", explain_unknown_move("banana", 13, 15));
+}
+
+#[test]
+fn outside_of_map_test() {
+    assert_eq!(None, outside_of_map(0, 0, 5, 6));
+    assert_eq!(Some("Column 7 is outside this 6-wide board.".to_string()), outside_of_map(0, 6, 5, 6));
+    assert_eq!(Some("Row F is outside this 5-tall board.".to_string()), outside_of_map(5, 0, 5, 6));
 }
 
 #[test]
@@ -570,7 +1057,69 @@ fn translate_move_test() {
     assert_eq!(MoveType::Defuse{row: 6, column: 4}, translate_move("def 5g"));
     assert_eq!(MoveType::Mark{row: 6, column: 1}, translate_move("mark 2g"));
     assert_eq!(MoveType::Mark{row: 2, column: 13}, translate_move("mark   c14   "));
+    assert_eq!(MoveType::Reveal{row: 3, column: 2}, translate_move("reveal d3"));
+    assert_eq!(MoveType::Chord{row: 0, column: 4}, translate_move("chord a5"));
     assert_eq!(MoveType::Unknown, translate_move("help"));
+    assert_eq!(MoveType::Unknown, translate_move("def"));
+}
+
+#[test]
+fn check_aliases_test() {
+    let config: HashMap<String, String> = [("f", "def"), ("F", "hint"), ("R", "reveal"), ("?", "mark"), ("x", "fly"), ("b2", "hint"), ("s", "stat"), ("bye", "exit")]
+        .into_iter()
+        .map(|(alias, target)| (alias.to_string(), target.to_string()))
+        .collect();
+    let (checked, warnings) = check_aliases(&config);
+
+    assert_eq!(vec![
+        ("?".to_string(), Command::Mark),
+        ("f".to_string(), Command::Hint),
+        ("r".to_string(), Command::Reveal),
+        ("bye".to_string(), Command::Quit),
+    ], checked);
+    assert_eq!(vec![
+        "The alias 'b2' looks like a position on the map.",
+        "The alias 'f' is set for both 'hint' and 'def'.",
+        "The alias 's' is already used before choosing the map size.",
+        "The alias 'x' is for 'fly', which is not a command.",
+    ], warnings);
+
+    // the '?' of the help goes to the alias
+    assert!(is_command_of("?", Command::Mark, &checked));
+    assert!(!is_command_of("?", Command::Help, &checked));
+    assert!(is_command_of("help", Command::Help, &checked));
+    assert!(is_command_of("?", Command::Help, &[]));
+}
+
+#[test]
+fn counts_as_click_test() {
+    assert!(translate_move("hint").is_hint());
+    assert!(!translate_move("A1").is_hint());
+    assert_eq!(Difficulty::Medium, Difficulty::of_size(10, 8));
+    assert_eq!(Difficulty::Custom, Difficulty::of_size(8, 10));
+    assert!(translate_move("A1").is_click());
+    assert!(translate_move("def B2").is_click());
+    assert!(translate_move("mark c3").is_click());
+    assert!(!translate_move("hint").is_click());
+    assert!(!translate_move("help").is_click());
+}
+
+#[test]
+fn uses_flag_test() {
+    assert!(translate_move("def B2").uses_flag());
+    assert!(translate_move("mark c3").uses_flag());
+    assert!(!translate_move("c3").uses_flag());
+    assert!(!translate_move("hint").uses_flag());
+}
+
+#[test]
+fn no_flag_rule_test() {
+    let rules = GameRules { no_flag: true, ..Default::default() };
+    let mut test_map = vec![vec![TileState::HiddenEmpty(0), TileState::Mine]];
+
+    assert!(process_input("def a2", translate_move("def a2"), &mut test_map, &rules).safe);
+    assert!(process_input("mark a1", translate_move("mark a1"), &mut test_map, &rules).safe);
+    assert_eq!(vec![vec![TileState::HiddenEmpty(0), TileState::Mine]], test_map);
 }
 
 #[test]
@@ -591,4 +1140,54 @@ fn double_defuse() {
     defuse_tile(0, 0, &mut test_map);
 
     assert_eq!(test_map[0][0], TileState::HiddenEmpty(0));
-}
\ No newline at end of file
+}
+
+#[test]
+fn reveal_cascade_test() {
+    let mut test_map = vec![
+        vec![TileState::HiddenEmpty(0), TileState::HiddenEmpty(1), TileState::Mine],
+        vec![TileState::HiddenEmpty(0), TileState::HiddenEmpty(1), TileState::HiddenEmpty(1)],
+    ];
+    let (result, opened) = reveal_tile(0, 0, &mut test_map, false);
+    assert!(matches!(result, MoveResult::SafeMove));
+    assert_eq!(HashSet::from([(0, 0), (0, 1), (1, 0), (1, 1)]), opened);
+    assert_eq!(TileState::HiddenEmpty(1), test_map[1][2]);
+
+    // nothing changes the second time
+    let (result, opened) = reveal_tile(0, 0, &mut test_map, false);
+    assert!(matches!(result, MoveResult::AlreadyRevealed));
+    assert!(opened.is_empty());
+
+    let report = process_input("b3", translate_move("b3"), &mut test_map, &GameRules::default());
+    assert!(report.safe);
+    assert_eq!(Some((1, 2)), report.tile);
+    assert_eq!(HashSet::from([(1, 2)]), report.opened);
+}
+
+#[test]
+fn chord_test() {
+    let mut test_map = vec![
+        vec![TileState::VisibleEmpty(1), TileState::Mine, TileState::HiddenEmpty(1)],
+        vec![TileState::HiddenEmpty(1), TileState::HiddenEmpty(1), TileState::HiddenEmpty(1)],
+    ];
+
+    // the mine is not defused yet
+    assert!(matches!(chord_tile(0, 0, &mut test_map).0, MoveResult::MakesNoSense));
+    assert!(matches!(chord_tile(1, 1, &mut test_map).0, MoveResult::MakesNoSense));
+
+    defuse_tile(0, 1, &mut test_map);
+    let (result, opened) = chord_tile(0, 0, &mut test_map);
+    assert!(matches!(result, MoveResult::SafeMove));
+    assert_eq!(HashSet::from([(1, 0), (1, 1)]), opened);
+    assert_eq!(TileState::VisibleEmpty(1), test_map[1][0]);
+    assert_eq!(TileState::VisibleEmpty(1), test_map[1][1]);
+    assert_eq!(TileState::HiddenEmpty(1), test_map[0][2]);
+
+    // a wrong defuser blows up the real mine
+    let mut test_map = vec![
+        vec![TileState::VisibleEmpty(1), TileState::Mine],
+        vec![TileState::Marked(1), TileState::HiddenEmpty(1)],
+    ];
+    assert!(matches!(chord_tile(0, 0, &mut test_map).0, MoveResult::Explosion));
+    assert_eq!(TileState::Explosion, test_map[0][1]);
+}
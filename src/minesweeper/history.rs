@@ -0,0 +1,116 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use super::stats::{data_file, GameRecord, GameResult};
+
+/// One line of the history, a finished or abandoned game
+///
+/// Training games are left out, like from the stats.
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct HistoryRecord {
+    pub timestamp: u64,     // seconds since the unix epoch
+    pub seed: u64,
+    pub width: u8,
+    pub height: u8,
+    pub mines: usize,
+    pub result: GameResult,
+    pub duration_ms: u64,
+    /// The clicks of the player, like in the stats
+    pub moves: usize,
+    pub hints: usize,
+    pub efficiency: f64,
+    pub bbbv: usize,
+    pub no_flag: bool,
+    /// The numbers added to the lifetime totals by this game
+    pub defused: usize,
+    pub revealed: usize,
+}
+
+impl HistoryRecord {
+    pub fn new(record: &GameRecord, seed: u64, hints: usize, defused: usize, revealed: usize) -> HistoryRecord {
+        HistoryRecord {
+            timestamp: record.timestamp,
+            seed,
+            width: record.width,
+            height: record.height,
+            mines: record.mines,
+            result: record.result,
+            duration_ms: record.duration_ms,
+            moves: record.clicks,
+            hints,
+            efficiency: record.efficiency(),
+            bbbv: record.bbbv,
+            no_flag: record.no_flag,
+            defused,
+            revealed,
+        }
+    }
+
+    /// The game as the stats keep it
+    pub fn game_record(&self) -> GameRecord {
+        GameRecord {
+            timestamp: self.timestamp,
+            width: self.width,
+            height: self.height,
+            mines: self.mines,
+            result: self.result,
+            duration_ms: self.duration_ms,
+            clicks: self.moves,
+            bbbv: self.bbbv,
+            no_flag: self.no_flag,
+        }
+    }
+}
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// The path of the history, next to the stats
+pub fn history_file() -> Option<PathBuf> {
+    data_file(HISTORY_FILE)
+}
+
+/// Adds a game to the end of the history, the earlier lines are never touched
+pub fn append_history(path: &Path, record: &HistoryRecord) -> io::Result<()> {
+    let line = serde_json::to_string(record)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{line}")
+}
+
+/// All the games of the history, empty if there is no history yet
+pub fn read_history(path: &Path) -> Vec<HistoryRecord> {
+    fs::read_to_string(path).map_or(Vec::new(), |text| parse_history(&text))
+}
+
+/// The records of the lines, a broken line (like the last one after a crash) is skipped
+fn parse_history(text: &str) -> Vec<HistoryRecord> {
+    text.lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+#[test]
+fn history_line_test() {
+    let game = GameRecord {
+        timestamp: 1700000000,
+        width: 6,
+        height: 5,
+        mines: 6,
+        result: GameResult::Won,
+        duration_ms: 4000,
+        clicks: 10,
+        bbbv: 8,
+        no_flag: true,
+    };
+    let record = HistoryRecord::new(&game, 42, 1, 6, 24);
+    assert_eq!(0.8, record.efficiency);
+    assert_eq!(10, record.moves);
+
+    let line = serde_json::to_string(&record).unwrap();
+    assert!(!line.contains('\n'));
+    let text = format!("{line}\n{line}\n{{\"timestamp\":17");
+    let history = parse_history(&text);
+    assert_eq!(vec![record.clone(), record.clone()], history);
+    assert_eq!(game.duration_ms, history[0].game_record().duration_ms);
+    assert_eq!(game.bbbv, history[0].game_record().bbbv);
+}
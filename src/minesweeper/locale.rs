@@ -0,0 +1,667 @@
+use std::fmt::Display;
+use std::sync::Mutex;
+
+/// The languages of the texts, English is the one in the code
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Language {
+    English,
+    Hungarian,
+    German,
+}
+
+impl Language {
+    /// Reads a language code or name, a locale like "hu_HU.UTF-8" works too
+    pub fn from_name(name: &str) -> Option<Language> {
+        let name = name.trim().to_lowercase();
+        let code = name.split(['_', '.', '-', '@']).next().unwrap_or("");
+        match code {
+            "en" | "english" => Some(Language::English),
+            "hu" | "magyar" | "hungarian" => Some(Language::Hungarian),
+            "de" | "deutsch" | "german" => Some(Language::German),
+            _ => None,
+        }
+    }
+
+    /// The language of the locale in the environment, English if it is not set or not translated
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Language {
+        ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+            .filter_map(|name| var(name))
+            .find(|value| !value.is_empty())
+            .and_then(|value| Language::from_name(&value))
+            .unwrap_or(Language::English)
+    }
+
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => &[],
+            Language::Hungarian => HUNGARIAN,
+            Language::German => GERMAN,
+        }
+    }
+
+    /// The extra command words, keyed by the first English word of the command
+    fn words(&self) -> &'static [(&'static str, &'static [&'static str])] {
+        match self {
+            Language::English => &[],
+            Language::Hungarian => HUNGARIAN_WORDS,
+            Language::German => GERMAN_WORDS,
+        }
+    }
+}
+
+/// The language of the texts, the one of the system is set first and the config can change it
+static LANGUAGE: Mutex<Language> = Mutex::new(Language::English);
+
+pub fn set_language(language: Language) {
+    *LANGUAGE.lock().unwrap() = language;
+}
+
+pub fn language() -> Language {
+    *LANGUAGE.lock().unwrap()
+}
+
+/// The text in the language of the game, the English text is its key in the catalog
+pub fn tr(english: &'static str) -> &'static str {
+    translate(language(), english)
+}
+
+fn translate(language: Language, english: &'static str) -> &'static str {
+    language.catalog().iter()
+        .find(|(key, _)| *key == english)
+        .map_or(english, |(_, text)| text)
+}
+
+/// Translates a text and fills its marks with the arguments
+///
+/// `{}` takes the next argument, `{0}` takes the first one, so a translation can change the order.
+pub fn trf(english: &'static str, args: &[&dyn Display]) -> String {
+    fill(tr(english), args)
+}
+
+fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut text = String::new();
+    let mut next = 0;
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        let mark = &rest[start + 1..end];
+        let index = if mark.is_empty() {
+            next += 1;
+            Some(next - 1)
+        } else {
+            mark.parse::<usize>().ok()
+        };
+        match index.and_then(|index| args.get(index)) {
+            Some(arg) => text.push_str(&arg.to_string()),
+            None => text.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+    text
+}
+
+/// The translated words of a command, next to the English ones
+///
+/// The command is named by its first English word, like "q" or "def".
+pub fn local_words(first_word: &str) -> &'static [&'static str] {
+    language().words().iter()
+        .find(|(key, _)| *key == first_word)
+        .map_or(&[], |(_, words)| words)
+}
+
+static HUNGARIAN_WORDS: &[(&str, &[&str])] = &[
+    ("q", &["kilép", "kilépés", "vége"]),
+    ("credits", &["készítők"]),
+    ("about", &["névjegy"]),
+    ("help", &["súgó", "segítség"]),
+    ("stat", &["statisztika"]),
+    ("restart", &["újra", "újrakezd"]),
+    ("hint", &["tipp", "segíts"]),
+    ("reveal", &["felfed"]),
+    ("def", &["hatástalanít", "zászló"]),
+    ("mark", &["jelöl"]),
+    ("chord", &["akkord"]),
+    ("training", &["gyakorlás", "edzés"]),
+    ("nf", &["zászlómentes", "zászló nélkül"]),
+    ("s", &["k", "kicsi", "kis"]),
+    ("m", &["közepes"]),
+    ("l", &["n", "nagy"]),
+    ("xl", &["óriás", "extra nagy"]),
+    ("y", &["i", "igen"]),
+    ("n", &["nem"]),
+];
+
+static GERMAN_WORDS: &[(&str, &[&str])] = &[
+    ("q", &["beenden", "ende"]),
+    ("credits", &["mitwirkende"]),
+    ("about", &["info"]),
+    ("help", &["hilfe"]),
+    ("stat", &["statistik"]),
+    ("restart", &["neustart", "neu"]),
+    ("hint", &["tipp"]),
+    ("reveal", &["aufdecken"]),
+    ("def", &["entschärfen", "flagge"]),
+    ("mark", &["markieren"]),
+    ("chord", &["akkord"]),
+    ("training", &["übung", "üben"]),
+    ("nf", &["ohne flaggen"]),
+    ("s", &["k", "klein"]),
+    ("m", &["mittel"]),
+    ("l", &["g", "groß", "gross"]),
+    ("xl", &["riesig", "extra groß"]),
+    ("y", &["j", "ja"]),
+    ("n", &["nein"]),
+];
+
+static HUNGARIAN: &[(&str, &str)] = &[
+    ("'{}' is not a position, type {} - like '{}'.", "A(z) '{}' nem pozíció, írd be {} - például '{}'."),
+    ("'{}' needs a position too, like '{} {}'.", "A(z) '{}' után pozíció is kell, például '{} {}'."),
+    ("(nobody)", "(senki)"),
+    (", or ", ", vagy "),
+    ("1 adjacent mine", "1 szomszédos akna"),
+    ("1 defuser", "1 hatástalanító"),
+    ("1 flagged neighbour", "1 megjelölt szomszéd"),
+    ("1 hidden neighbour", "1 rejtett szomszéd"),
+    ("1 mine", "1 akna"),
+    ("1 number", "1 szám"),
+    ("1 tile", "1 mező"),
+    ("Already revealed...", "Ez már fel van fedve..."),
+    ("Avg", "Átlag"),
+    ("Best", "Legjobb"),
+    ("Clicks: {}, efficiency: {}%", "Kattintások: {}, hatékonyság: {}%"),
+    ("Column {} is outside this {}-wide board.", "A(z) {} oszlop kívül esik a {} oszlopos táblán."),
+    ("Columns {}-{} and rows {}-{} of the {}x{} map", "Oszlopok: {}-{}, sorok: {}-{}, a pálya {}x{}"),
+    ("Congrats!", "Gratulálok!"),
+    ("Could not save the map to {}. {}", "Nem sikerült ide menteni a pályát: {}. {}"),
+    ("Defused: {}", "Hatástalanítva: {}"),
+    ("Did you mean '{} {}'?", "Erre gondoltál: '{} {}'?"),
+    ("Did you mean '{} {}'? Also {}", "Erre gondoltál: '{} {}'? Ráadásul {}"),
+    ("Did you mean '{}'?", "Erre gondoltál: '{}'?"),
+    ("Difficulty: {}", "Nehézség: {}"),
+    ("Do you want to start again? (y/n)", "Szeretnél újat kezdeni? (i/n)"),
+    ("Error on reading the config file {}. {}", "Hiba a(z) {} beállításfájl olvasásakor. {}"),
+    ("Exploded: {}", "Robbanások: {}"),
+    ("Failed to back up the stats. {}", "Nem sikerült biztonsági másolatot készíteni a statisztikáról. {}"),
+    ("Failed to read the stats. {}", "Nem sikerült beolvasni a statisztikát. {}"),
+    ("Failed to save the game to the history. {}", "Nem sikerült a játékot menteni az előzményekbe. {}"),
+    ("Failed to save the stats. {}", "Nem sikerült menteni a statisztikát. {}"),
+    ("Flags are off in no flag mode, you can only reveal tiles.", "Zászló nélküli módban nincsenek zászlók, csak felfedni lehet."),
+    ("For a web page type 'export html', or 'export html steps' to step through the moves - like \"export html steps game.html\"",
+        "Weboldalhoz írd be: 'export html', vagy 'export html steps', hogy a lépéseket is végig lehessen nézni - például \"export html steps jatek.html\""),
+    ("Games", "Játékok"),
+    ("Games: {}, won {}", "Játékok: {}, nyert {}"),
+    ("Graphics designer:", "Grafikus:"),
+    ("Hello, minesweeper!", "Szia, aknakereső!"),
+    ("Hints: {}", "Tippek: {}"),
+    ("How big map would you like? s, m, l, xl", "Mekkora pályát szeretnél? s, m, l, xl"),
+    ("I don't understand '{}'.", "Nem értem ezt: '{}'."),
+    ("I don't understand '{}'. Type {} to see the commands.", "Nem értem ezt: '{}'. Írd be: {}, hogy lásd a parancsokat."),
+    ("I don't understand this.", "Ezt nem értem."),
+    ("I don't understand this: {}. Type {} to set map size or {} to quit", "Nem értem ezt: {}. Írd be: {} a pálya méretéhez, vagy {} a kilépéshez"),
+    ("If you want to close the game, type {}", "Ha ki akarsz lépni a játékból, írd be: {}"),
+    ("If you want to restart the game, type {}", "Ha újra akarod kezdeni a játékot, írd be: {}"),
+    ("In training you have played {} games, won {}, and rewound {} explosions",
+        "Gyakorlásban {} játékot játszottál, ebből {} győzelem, és {} robbanást tekertél vissza"),
+    ("Longest", "Leghosszabb"),
+    ("Lost", "Vesztett"),
+    ("Mines left: {}", "Hátralévő aknák: {}"),
+    ("No flag mode is off.", "A zászló nélküli mód ki van kapcsolva."),
+    ("No flag mode is on, you can't use 'def' and 'mark' in the next games.",
+        "A zászló nélküli mód be van kapcsolva, a következő játékokban nem használhatod a 'def' és 'mark' parancsot."),
+    ("No revealed number has hidden tiles around it.", "Egyik felfedett szám körül sincs rejtett mező."),
+    ("Only a revealed number with the same number of defusers around it can be chorded.",
+        "Csak olyan felfedett számra lehet akkordot használni, amely körül ugyanannyi hatástalanító van."),
+    ("Open source projects and their license used in this game:", "A játékban használt nyílt forráskódú projektek és licencük:"),
+    ("Opened {} tiles.", "{} mező nyílt ki."),
+    ("Programmer:", "Programozó:"),
+    ("Progress: {}%", "Haladás: {}%"),
+    ("Progress: {}/{}", "Haladás: {}/{}"),
+    ("Revealed: {}", "Felfedve: {}"),
+    ("Row {} is outside this {}-tall board.", "A(z) {} sor kívül esik a {} soros táblán."),
+    ("Row {}: {}.", "A(z) {} sor: {}."),
+    ("Seed: {}", "Mag: {}"),
+    ("Size", "Méret"),
+    ("Stats:", "Statisztika:"),
+    ("Streak", "Sorozat"),
+    ("Success! All mines defused!", "Siker! Minden akna hatástalanítva!"),
+    ("That tile is defused, type 'def' with the position to remove the defuser first.",
+        "Ez a mező hatástalanítva van, előbb vedd le a hatástalanítót a 'def' paranccsal és a pozícióval."),
+    ("That tile was provably a mine, the numbers around it give it away.", "Az a mező bizonyíthatóan akna volt, a körülötte lévő számok elárulták."),
+    ("That was a bad gamble, it had a {}% chance to be a mine and there was a safer option.",
+        "Rossz kockázat volt, {}% eséllyel volt akna, és volt biztonságosabb lehetőség."),
+    ("That was a forced guess, there was no safe tile to find. It had a {}% chance to be a mine.",
+        "Kényszerű tipp volt, nem volt biztosan biztonságos mező. {}% eséllyel volt akna."),
+    ("That was a mine. Game over.", "Ez akna volt. Vége a játéknak."),
+    ("That was a mine. Rewinding the move.", "Ez akna volt. A lépés visszatekerve."),
+    ("The alias '{}' can't be empty or contain spaces.", "A(z) '{}' álnév nem lehet üres és nem tartalmazhat szóközt."),
+    ("The alias '{}' is already a command for '{}'.", "A(z) '{}' álnév már a(z) '{}' parancs szava."),
+    ("The alias '{}' is already used before choosing the map size.", "A(z) '{}' álnév már foglalt a pálya méretének választásánál."),
+    ("The alias '{}' is for '{}', which is not a command.", "A(z) '{}' álnév a(z) '{}' szóhoz tartozik, ami nem parancs."),
+    ("The alias '{}' is set for both '{}' and '{}'.", "A(z) '{}' álnév a(z) '{}' és a(z) '{}' parancshoz is be van állítva."),
+    ("The alias '{}' looks like a position on the map.", "A(z) '{}' álnév úgy néz ki, mint egy pozíció a pályán."),
+    ("The config file {} is not valid, using the defaults. {}", "A(z) {} beállításfájl hibás, az alapértékeket használom. {}"),
+    ("The coordinates are '{}' now, like '{}'.", "A koordináták most: '{}', például '{}'."),
+    ("The coordinates are '{}'. They can be: {}.", "A koordináták: '{}'. Lehetnek: {}."),
+    ("The defuser is removed from {}.", "A hatástalanító lekerült innen: {}."),
+    ("The full screen mode is not working, switching to lines. {}", "A teljes képernyős mód nem működik, soros módra váltok. {}"),
+    ("The map is saved to {}.", "A pálya ide mentve: {}."),
+    ("The mine exploded at {}. {}", "Az akna itt robbant: {}. {}"),
+    ("The question mark is removed from {}.", "A kérdőjel lekerült innen: {}."),
+    ("The stats are saved by a newer version of the game (version {} of the file, this game knows {}), they are not changed.",
+        "A statisztikát a játék egy újabb verziója mentette (a fájl {}. verziójú, ez a játék a(z) {}. verzióig ismeri), nem változtatok rajta."),
+    ("The stats can't be read ({}), the file is moved to {} and the stats are counted again from the history.",
+        "A statisztika nem olvasható ({}), a fájl ide került: {}, és a statisztika újra összeszámolódik az előzményekből."),
+    ("The theme is '{}' now.", "A téma most: '{}'."),
+    ("The theme is '{}'. The themes are: {}.", "A téma: '{}'. A témák: {}."),
+    ("The whole map is on the screen already.", "Az egész pálya már a képernyőn van."),
+    ("The zoom is '{}' now.", "A nagyítás most: '{}'."),
+    ("The zoom is '{}'. It can be: {}, or 'zoom in' and 'zoom out'.", "A nagyítás: '{}'. Lehet: {}, vagy 'zoom in' és 'zoom out'."),
+    ("There are no coordinates called '{}'. Type 'coords' to see them.", "Nincs '{}' nevű koordináta. Írd be a 'coords' parancsot, hogy lásd őket."),
+    ("There is no language called '{}', using the language of the system.", "Nincs '{}' nevű nyelv, a rendszer nyelvét használom."),
+    ("There is no theme called '{}', using the classic colors.", "Nincs '{}' nevű téma, a klasszikus színeket használom."),
+    ("There is no theme called '{}'. Type 'theme' to see them.", "Nincs '{}' nevű téma. Írd be a 'theme' parancsot, hogy lásd őket."),
+    ("There is no zoom called '{}'. Type 'zoom' to see them.", "Nincs '{}' nevű nagyítás. Írd be a 'zoom' parancsot, hogy lásd őket."),
+    ("Time: {}s", "Idő: {} mp"),
+    ("To change the colors, type 'theme' with the name of a theme - like \"theme deuteranopia-safe\"",
+        "A színek megváltoztatásához írd be a 'theme' parancsot egy téma nevével - például \"theme deuteranopia-safe\""),
+    ("To defuse a mine, type {} with the position - like \"def {}\" or \"def {}\". It will be shown as a '.' (dot)",
+        "Egy akna hatástalanításához írd be: {} és a pozíciót - például \"def {}\" vagy \"def {}\". '.' (pont) jelzi majd"),
+    ("To draw the tiles smaller or bigger, type 'zoom' with 'compact', 'normal' or 'large', or 'zoom in' and 'zoom out'",
+        "A mezők kisebb vagy nagyobb rajzolásához írd be a 'zoom' parancsot ezek egyikével: 'compact', 'normal' vagy 'large', vagy 'zoom in' és 'zoom out'"),
+    ("To hear about the map, type \"describe\" with a position, \"row\" with a row name or \"frontier\" - like \"describe {}\"",
+        "A pálya leírásához írd be: \"describe\" és egy pozíciót, \"row\" és egy sor nevét, vagy \"frontier\" - például \"describe {}\""),
+    ("To mark as a potential mine, type {} with the position - like \"mark {}\" or \"mark {}\". It will be shown as a '?' (question mark)",
+        "Egy lehetséges akna megjelöléséhez írd be: {} és a pozíciót - például \"mark {}\" vagy \"mark {}\". '?' (kérdőjel) jelzi majd"),
+    ("To name the tiles in another way, type 'coords' with 'letters', 'spreadsheet' or 'numeric'",
+        "A mezők más elnevezéséhez írd be a 'coords' parancsot ezek egyikével: 'letters', 'spreadsheet' vagy 'numeric'"),
+    ("To reveal a tile, type {} - like \"{}\" or \"{}\"", "Egy mező felfedéséhez írd be {} - például \"{}\" vagy \"{}\""),
+    ("To reveal the neighbours of a number which has all its mines defused, type {} with the position - like \"chord {}\"",
+        "Egy olyan szám szomszédainak felfedéséhez, amelynek minden aknája hatástalanítva van, írd be: {} és a pozíciót - például \"chord {}\""),
+    ("To save the map as an image, type 'export svg' with the name of the file - like \"export svg map.svg\"",
+        "A pálya képként való mentéséhez írd be: 'export svg' és a fájl nevét - például \"export svg palya.svg\""),
+    ("Training game finished with {} rewinds.", "A gyakorló játék véget ért, {} visszatekeréssel."),
+    ("Training mode is off.", "A gyakorló mód ki van kapcsolva."),
+    ("Training mode is on, hitting a mine rewinds the move. These games are not counted in your stats.",
+        "A gyakorló mód be van kapcsolva, az aknára lépés visszatekeri a lépést. Ezek a játékok nem számítanak bele a statisztikádba."),
+    ("Type 'def' with position to remove the defuser.", "A hatástalanító levételéhez írd be a 'def' parancsot a pozícióval."),
+    ("Type 'describe' with a position on the map, like 'describe {}'.",
+        "Írd be a 'describe' parancsot egy pozícióval a pályán, például 'describe {}'."),
+    ("Type 'export svg' or 'export html' with the name of the file, like 'export svg map.svg'. With 'export html steps' the page can step through the moves.",
+        "Írd be az 'export svg' vagy az 'export html' parancsot a fájl nevével, például 'export svg palya.svg'. Az 'export html steps' oldalán a lépéseket is végig lehet nézni."),
+    ("Type 'pan' with left, right, up or down and the number of tiles, or with a position to look at, like 'pan right 5' or 'pan C30'.",
+        "Írd be a 'pan' parancsot left, right, up vagy down iránnyal és a mezők számával, vagy egy pozícióval, például 'pan right 5' vagy 'pan C30'."),
+    ("Type 'row' with the name of a row, from {} to {}.", "Írd be a 'row' parancsot egy sor nevével, {} és {} között."),
+    ("Type \"def\" with the position again to remove the defuser.", "A hatástalanító levételéhez írd be újra a \"def\" parancsot a pozícióval."),
+    ("Type a position like '{}', or 'help' to see the commands.", "Írj be egy pozíciót, például '{}', vagy a 'help' parancsot a parancsok listájához."),
+    ("Type {} to switch training mode, or {} to play without flags", "Írd be: {} a gyakorló módhoz, vagy {} a zászló nélküli játékhoz"),
+    ("Unknown color mode '{}'. It can be 'always', 'never' or 'auto'.", "Ismeretlen színmód: '{}'. Lehet 'always', 'never' vagy 'auto'."),
+    ("Unknown coordinates '{}'. They can be 'letters', 'spreadsheet' or 'numeric'.",
+        "Ismeretlen koordináták: '{}'. Lehetnek 'letters', 'spreadsheet' vagy 'numeric'."),
+    ("Unknown glyphs '{}'. They can be 'ascii', 'unicode' or 'emoji'.", "Ismeretlen karakterkészlet: '{}'. Lehet 'ascii', 'unicode' vagy 'emoji'."),
+    ("Unknown language '{}'. It can be 'en', 'hu' or 'de'.", "Ismeretlen nyelv: '{}'. Lehet 'en', 'hu' vagy 'de'."),
+    ("Unknown option '{}'. The options are '--line', '--tui', '--accessible', '--color=always|never|auto', '--lang=en|hu|de', '--glyphs=ascii|unicode|emoji', '--coords=letters|spreadsheet|numeric' and '--zoom=compact|normal|large'.",
+        "Ismeretlen kapcsoló: '{}'. A kapcsolók: '--line', '--tui', '--accessible', '--color=always|never|auto', '--lang=en|hu|de', '--glyphs=ascii|unicode|emoji', '--coords=letters|spreadsheet|numeric' és '--zoom=compact|normal|large'."),
+    ("Unknown zoom '{}'. It can be 'compact', 'normal' or 'large'.", "Ismeretlen nagyítás: '{}'. Lehet 'compact', 'normal' vagy 'large'."),
+    ("Win %", "Arány"),
+    ("Without flags you have won {} of {} games, your best time is {}s and best speed is {} 3BV/s",
+        "Zászló nélkül {1} játékból {0} győzelmed van, a legjobb időd {2} mp, a legjobb sebességed {3} 3BV/s"),
+    ("Won", "Nyert"),
+    ("You can use some hints, type {} to reveal a random tile", "Kérhetsz tippet is, írd be: {}, és felfedek egy véletlen mezőt"),
+    ("You have defused {} mines", "{} aknát hatástalanítottál"),
+    ("You have exploded {} times", "{} alkalommal robbantál fel"),
+    ("You have revealed {} safe tiles", "{} biztonságos mezőt fedtél fel"),
+    ("You won without using a single flag (NF)!", "Egyetlen zászló nélkül nyertél (NF)!"),
+    ("Your aliases:", "Az álneveid:"),
+    ("Your best speed is {} 3BV/s with {}% efficiency", "A legjobb sebességed {} 3BV/s, {}% hatékonysággal"),
+    ("Your task is to defuse all the mines.", "A feladatod, hogy minden aknát hatástalaníts."),
+    ("arrows/hjkl: move  HJKL: pan  space: reveal  f: defuse  ?: mark  c: chord  t: theme  +/-: zoom  n: restart  q: quit  :: command",
+        "nyilak/hjkl: mozgás  HJKL: görgetés  szóköz: felfedés  f: hatástalanítás  ?: jelölés  c: akkord  t: téma  +/-: nagyítás  n: újra  q: kilépés  :: parancs"),
+    ("column {} is outside this {}-wide board.", "a(z) {} oszlop kívül esik a {} oszlopos táblán."),
+    ("custom {}, {}", "egyéni {}, {}"),
+    ("custom {}x{}", "egyedi {}x{}"),
+    ("defused", "hatástalanítva"),
+    ("defused mine", "hatástalanított akna"),
+    ("defused, {}", "hatástalanítva, {}"),
+    ("dense", "sűrű"),
+    ("empty", "üres"),
+    ("exploded mine", "felrobbant akna"),
+    ("extra large", "óriás"),
+    ("hidden", "rejtett"),
+    ("hidden with a question mark, {}", "rejtett, kérdőjellel, {}"),
+    ("hidden, {}", "rejtett, {}"),
+    ("large", "nagy"),
+    ("medium", "közepes"),
+    ("mine under a question mark", "akna kérdőjel alatt"),
+    ("missed mine", "kimaradt akna"),
+    ("mouse: left reveals, right defuses, middle or both buttons chord",
+        "egér: a bal felfed, a jobb hatástalanít, a középső vagy mindkét gomb akkord"),
+    ("no adjacent mines", "nincs szomszédos akna"),
+    ("no defusers", "nincs hatástalanító"),
+    ("no flagged neighbours", "nincs megjelölt szomszéd"),
+    ("no hidden neighbours", "nincs rejtett szomszéd"),
+    ("no mines", "nincs akna"),
+    ("no numbers", "nincs szám"),
+    ("no tiles", "nincs mező"),
+    ("normal", "normál"),
+    ("number {}", "szám: {}"),
+    ("question mark", "kérdőjel"),
+    ("revealed, {}, {}", "felfedve, {}, {}"),
+    ("row {} is outside this {}-tall board.", "a(z) {} sor kívül esik a {} soros táblán."),
+    ("small", "kicsi"),
+    ("sparse", "ritka"),
+    ("the column letter and the row number", "az oszlop betűjét és a sor számát"),
+    ("the columns start from 1.", "az oszlopok 1-től kezdődnek."),
+    ("the exploded mine", "a felrobbant akna"),
+    ("the row and the column number with a comma", "a sor és az oszlop számát vesszővel"),
+    ("the row letter and the column number", "a sor betűjét és az oszlop számát"),
+    ("the rows start from 1.", "a sorok 1-től kezdődnek."),
+    ("wrong defuser", "rossz hatástalanító"),
+    ("{} adjacent mines", "{} szomszédos akna"),
+    ("{} are revealed.", "{} felfedve."),
+    ("{} defused.", "{} hatástalanítva."),
+    ("{} defusers", "{} hatástalanító"),
+    ("{} flagged neighbours", "{} megjelölt szomszéd"),
+    ("{} for '{}'", "{} a(z) '{}' helyett"),
+    ("{} hidden neighbours", "{} rejtett szomszéd"),
+    ("{} is defused.", "{} hatástalanítva."),
+    ("{} is marked with a question mark.", "{} kérdőjellel megjelölve."),
+    ("{} is {}.", "{}: {}."),
+    ("{} left: {}.", "{} maradt: {}."),
+    ("{} mines", "{} akna"),
+    ("{} numbers", "{} szám"),
+    ("{} of {} safe tiles are revealed.", "{1} biztonságos mezőből {0} van felfedve."),
+    ("{} on safe tiles: {}.", "{} biztonságos mezőn: {}."),
+    ("{} on the frontier: {}.", "{} a határon: {}."),
+    ("{} tiles", "{} mező"),
+    ("{} to {} {}", "{}–{} {}"),
+    ("{} was a mine.", "{} akna volt."),
+];
+
+static GERMAN: &[(&str, &str)] = &[
+    ("'{}' is not a position, type {} - like '{}'.", "'{}' ist keine Position, gib {} ein - zum Beispiel '{}'."),
+    ("'{}' needs a position too, like '{} {}'.", "'{}' braucht auch eine Position, zum Beispiel '{} {}'."),
+    ("(nobody)", "(niemand)"),
+    (", or ", ", oder "),
+    ("1 adjacent mine", "1 angrenzende Mine"),
+    ("1 defuser", "1 Entschärfer"),
+    ("1 flagged neighbour", "1 markierter Nachbar"),
+    ("1 hidden neighbour", "1 verdeckter Nachbar"),
+    ("1 mine", "1 Mine"),
+    ("1 number", "1 Zahl"),
+    ("1 tile", "1 Feld"),
+    ("Already revealed...", "Schon aufgedeckt..."),
+    ("Avg", "Schnitt"),
+    ("Best", "Bestzeit"),
+    ("Clicks: {}, efficiency: {}%", "Klicks: {}, Effizienz: {}%"),
+    ("Column {} is outside this {}-wide board.", "Spalte {} liegt außerhalb dieses {} Spalten breiten Felds."),
+    ("Columns {}-{} and rows {}-{} of the {}x{} map", "Spalten {}-{} und Reihen {}-{} des {}x{}-Feldes"),
+    ("Congrats!", "Glückwunsch!"),
+    ("Could not save the map to {}. {}", "Das Feld konnte nicht nach {} gespeichert werden. {}"),
+    ("Defused: {}", "Entschärft: {}"),
+    ("Did you mean '{} {}'?", "Meintest du '{} {}'?"),
+    ("Did you mean '{} {}'? Also {}", "Meintest du '{} {}'? Außerdem: {}"),
+    ("Did you mean '{}'?", "Meintest du '{}'?"),
+    ("Difficulty: {}", "Schwierigkeit: {}"),
+    ("Do you want to start again? (y/n)", "Möchtest du noch einmal spielen? (j/n)"),
+    ("Error on reading the config file {}. {}", "Fehler beim Lesen der Konfigurationsdatei {}. {}"),
+    ("Exploded: {}", "Explodiert: {}"),
+    ("Failed to back up the stats. {}", "Die Statistik konnte nicht gesichert werden. {}"),
+    ("Failed to read the stats. {}", "Die Statistik konnte nicht gelesen werden. {}"),
+    ("Failed to save the game to the history. {}", "Das Spiel konnte nicht im Verlauf gespeichert werden. {}"),
+    ("Failed to save the stats. {}", "Die Statistik konnte nicht gespeichert werden. {}"),
+    ("Flags are off in no flag mode, you can only reveal tiles.", "Im Modus ohne Flaggen gibt es keine Flaggen, du kannst nur Felder aufdecken."),
+    ("For a web page type 'export html', or 'export html steps' to step through the moves - like \"export html steps game.html\"",
+        "Für eine Webseite gib 'export html' ein, oder 'export html steps', um die Züge durchzugehen - zum Beispiel \"export html steps spiel.html\""),
+    ("Games", "Spiele"),
+    ("Games: {}, won {}", "Spiele: {}, gewonnen {}"),
+    ("Graphics designer:", "Grafikdesign:"),
+    ("Hello, minesweeper!", "Hallo, Minesweeper!"),
+    ("Hints: {}", "Tipps: {}"),
+    ("How big map would you like? s, m, l, xl", "Wie groß soll das Feld sein? s, m, l, xl"),
+    ("I don't understand '{}'.", "Ich verstehe '{}' nicht."),
+    ("I don't understand '{}'. Type {} to see the commands.", "Ich verstehe '{}' nicht. Gib {} ein, um die Befehle zu sehen."),
+    ("I don't understand this.", "Das verstehe ich nicht."),
+    ("I don't understand this: {}. Type {} to set map size or {} to quit", "Das verstehe ich nicht: {}. Gib {} für die Feldgröße ein oder {} zum Beenden"),
+    ("If you want to close the game, type {}", "Um das Spiel zu beenden, gib {} ein"),
+    ("If you want to restart the game, type {}", "Um das Spiel neu zu starten, gib {} ein"),
+    ("In training you have played {} games, won {}, and rewound {} explosions",
+        "Im Training hast du {} Spiele gespielt, {} gewonnen und {} Explosionen zurückgespult"),
+    ("Longest", "Längste"),
+    ("Lost", "Niederlagen"),
+    ("Mines left: {}", "Minen übrig: {}"),
+    ("No flag mode is off.", "Der Modus ohne Flaggen ist aus."),
+    ("No flag mode is on, you can't use 'def' and 'mark' in the next games.",
+        "Der Modus ohne Flaggen ist an, in den nächsten Spielen gibt es kein 'def' und 'mark'."),
+    ("No revealed number has hidden tiles around it.", "Keine aufgedeckte Zahl hat verdeckte Felder um sich."),
+    ("Only a revealed number with the same number of defusers around it can be chorded.",
+        "Nur eine aufgedeckte Zahl mit genauso vielen Entschärfern ringsum kann einen Akkord auslösen."),
+    ("Open source projects and their license used in this game:", "In diesem Spiel verwendete Open-Source-Projekte und ihre Lizenzen:"),
+    ("Opened {} tiles.", "{} Felder aufgedeckt."),
+    ("Programmer:", "Programmierung:"),
+    ("Progress: {}%", "Fortschritt: {}%"),
+    ("Progress: {}/{}", "Fortschritt: {}/{}"),
+    ("Revealed: {}", "Aufgedeckt: {}"),
+    ("Row {} is outside this {}-tall board.", "Zeile {} liegt außerhalb dieses {} Zeilen hohen Felds."),
+    ("Row {}: {}.", "Reihe {}: {}."),
+    ("Seed: {}", "Seed: {}"),
+    ("Size", "Größe"),
+    ("Stats:", "Statistik:"),
+    ("Streak", "Serie"),
+    ("Success! All mines defused!", "Geschafft! Alle Minen entschärft!"),
+    ("That tile is defused, type 'def' with the position to remove the defuser first.",
+        "Dieses Feld ist entschärft, entferne den Entschärfer zuerst mit 'def' und der Position."),
+    ("That tile was provably a mine, the numbers around it give it away.", "Dieses Feld war nachweislich eine Mine, die Zahlen ringsum verraten es."),
+    ("That was a bad gamble, it had a {}% chance to be a mine and there was a safer option.",
+        "Das war ein schlechtes Risiko, mit {}% Wahrscheinlichkeit eine Mine, und es gab eine sicherere Wahl."),
+    ("That was a forced guess, there was no safe tile to find. It had a {}% chance to be a mine.",
+        "Das war ein erzwungener Tipp, es gab kein sicheres Feld. Es war mit {}% Wahrscheinlichkeit eine Mine."),
+    ("That was a mine. Game over.", "Das war eine Mine. Spiel vorbei."),
+    ("That was a mine. Rewinding the move.", "Das war eine Mine. Der Zug wird zurückgespult."),
+    ("The alias '{}' can't be empty or contain spaces.", "Der Alias '{}' darf nicht leer sein oder Leerzeichen enthalten."),
+    ("The alias '{}' is already a command for '{}'.", "Der Alias '{}' ist schon ein Befehl für '{}'."),
+    ("The alias '{}' is already used before choosing the map size.", "Der Alias '{}' wird schon bei der Wahl der Feldgröße verwendet."),
+    ("The alias '{}' is for '{}', which is not a command.", "Der Alias '{}' ist für '{}', das ist kein Befehl."),
+    ("The alias '{}' is set for both '{}' and '{}'.", "Der Alias '{}' ist sowohl für '{}' als auch für '{}' gesetzt."),
+    ("The alias '{}' looks like a position on the map.", "Der Alias '{}' sieht wie eine Position auf dem Feld aus."),
+    ("The config file {} is not valid, using the defaults. {}", "Die Konfigurationsdatei {} ist ungültig, es gelten die Standardwerte. {}"),
+    ("The coordinates are '{}' now, like '{}'.", "Die Koordinaten sind jetzt '{}', zum Beispiel '{}'."),
+    ("The coordinates are '{}'. They can be: {}.", "Die Koordinaten sind '{}'. Möglich sind: {}."),
+    ("The defuser is removed from {}.", "Der Entschärfer wurde von {} entfernt."),
+    ("The full screen mode is not working, switching to lines. {}", "Der Vollbildmodus funktioniert nicht, es geht zeilenweise weiter. {}"),
+    ("The map is saved to {}.", "Das Feld wurde nach {} gespeichert."),
+    ("The mine exploded at {}. {}", "Die Mine ist bei {} explodiert. {}"),
+    ("The question mark is removed from {}.", "Das Fragezeichen wurde von {} entfernt."),
+    ("The stats are saved by a newer version of the game (version {} of the file, this game knows {}), they are not changed.",
+        "Die Statistik wurde von einer neueren Version des Spiels gespeichert (Version {} der Datei, dieses Spiel kennt {}), sie wird nicht verändert."),
+    ("The stats can't be read ({}), the file is moved to {} and the stats are counted again from the history.",
+        "Die Statistik ist nicht lesbar ({}), die Datei wurde nach {} verschoben und die Statistik wird aus dem Verlauf neu gezählt."),
+    ("The theme is '{}' now.", "Das Farbschema ist jetzt '{}'."),
+    ("The theme is '{}'. The themes are: {}.", "Das Farbschema ist '{}'. Die Farbschemata sind: {}."),
+    ("The whole map is on the screen already.", "Das ganze Feld ist schon auf dem Bildschirm."),
+    ("The zoom is '{}' now.", "Der Zoom ist jetzt '{}'."),
+    ("The zoom is '{}'. It can be: {}, or 'zoom in' and 'zoom out'.", "Der Zoom ist '{}'. Möglich sind: {}, oder 'zoom in' und 'zoom out'."),
+    ("There are no coordinates called '{}'. Type 'coords' to see them.", "Es gibt keine Koordinaten namens '{}'. Gib 'coords' ein, um sie zu sehen."),
+    ("There is no language called '{}', using the language of the system.",
+        "Es gibt keine Sprache namens '{}', die Sprache des Systems wird verwendet."),
+    ("There is no theme called '{}', using the classic colors.", "Es gibt kein Farbschema namens '{}', die klassischen Farben werden verwendet."),
+    ("There is no theme called '{}'. Type 'theme' to see them.", "Es gibt kein Farbschema namens '{}'. Gib 'theme' ein, um sie zu sehen."),
+    ("There is no zoom called '{}'. Type 'zoom' to see them.", "Es gibt keinen Zoom namens '{}'. Gib 'zoom' ein, um sie zu sehen."),
+    ("Time: {}s", "Zeit: {}s"),
+    ("To change the colors, type 'theme' with the name of a theme - like \"theme deuteranopia-safe\"",
+        "Um die Farben zu ändern, gib 'theme' mit dem Namen eines Farbschemas ein - zum Beispiel \"theme deuteranopia-safe\""),
+    ("To defuse a mine, type {} with the position - like \"def {}\" or \"def {}\". It will be shown as a '.' (dot)",
+        "Um eine Mine zu entschärfen, gib {} mit der Position ein - zum Beispiel \"def {}\" oder \"def {}\". Sie wird als '.' (Punkt) angezeigt"),
+    ("To draw the tiles smaller or bigger, type 'zoom' with 'compact', 'normal' or 'large', or 'zoom in' and 'zoom out'",
+        "Um die Felder kleiner oder größer zu zeichnen, gib 'zoom' mit 'compact', 'normal' oder 'large' ein, oder 'zoom in' und 'zoom out'"),
+    ("To hear about the map, type \"describe\" with a position, \"row\" with a row name or \"frontier\" - like \"describe {}\"",
+        "Für eine Beschreibung des Felds gib \"describe\" mit einer Position, \"row\" mit einem Zeilennamen oder \"frontier\" ein - zum Beispiel \"describe {}\""),
+    ("To mark as a potential mine, type {} with the position - like \"mark {}\" or \"mark {}\". It will be shown as a '?' (question mark)",
+        "Um eine mögliche Mine zu markieren, gib {} mit der Position ein - zum Beispiel \"mark {}\" oder \"mark {}\". Sie wird als '?' (Fragezeichen) angezeigt"),
+    ("To name the tiles in another way, type 'coords' with 'letters', 'spreadsheet' or 'numeric'",
+        "Um die Felder anders zu benennen, gib 'coords' mit 'letters', 'spreadsheet' oder 'numeric' ein"),
+    ("To reveal a tile, type {} - like \"{}\" or \"{}\"", "Um ein Feld aufzudecken, gib {} ein - zum Beispiel \"{}\" oder \"{}\""),
+    ("To reveal the neighbours of a number which has all its mines defused, type {} with the position - like \"chord {}\"",
+        "Um die Nachbarn einer Zahl aufzudecken, deren Minen alle entschärft sind, gib {} mit der Position ein - zum Beispiel \"chord {}\""),
+    ("To save the map as an image, type 'export svg' with the name of the file - like \"export svg map.svg\"",
+        "Um das Feld als Bild zu speichern, gib 'export svg' mit dem Dateinamen ein - zum Beispiel \"export svg feld.svg\""),
+    ("Training game finished with {} rewinds.", "Trainingsspiel beendet, {} Mal zurückgespult."),
+    ("Training mode is off.", "Der Trainingsmodus ist aus."),
+    ("Training mode is on, hitting a mine rewinds the move. These games are not counted in your stats.",
+        "Der Trainingsmodus ist an, eine Mine spult den Zug zurück. Diese Spiele zählen nicht in deiner Statistik."),
+    ("Type 'def' with position to remove the defuser.", "Gib 'def' mit der Position ein, um den Entschärfer zu entfernen."),
+    ("Type 'describe' with a position on the map, like 'describe {}'.",
+        "Gib 'describe' mit einer Position auf dem Feld ein, zum Beispiel 'describe {}'."),
+    ("Type 'export svg' or 'export html' with the name of the file, like 'export svg map.svg'. With 'export html steps' the page can step through the moves.",
+        "Gib 'export svg' oder 'export html' mit dem Dateinamen ein, zum Beispiel 'export svg feld.svg'. Mit 'export html steps' kann die Seite die Züge durchgehen."),
+    ("Type 'pan' with left, right, up or down and the number of tiles, or with a position to look at, like 'pan right 5' or 'pan C30'.",
+        "Gib 'pan' mit left, right, up oder down und der Zahl der Felder ein, oder mit einer Position, zum Beispiel 'pan right 5' oder 'pan C30'."),
+    ("Type 'row' with the name of a row, from {} to {}.", "Gib 'row' mit dem Namen einer Reihe ein, von {} bis {}."),
+    ("Type \"def\" with the position again to remove the defuser.", "Gib \"def\" mit der Position noch einmal ein, um den Entschärfer zu entfernen."),
+    ("Type a position like '{}', or 'help' to see the commands.", "Gib eine Position ein, zum Beispiel '{}', oder 'help', um die Befehle zu sehen."),
+    ("Type {} to switch training mode, or {} to play without flags", "Gib {} ein, um den Trainingsmodus umzuschalten, oder {}, um ohne Flaggen zu spielen"),
+    ("Unknown color mode '{}'. It can be 'always', 'never' or 'auto'.", "Unbekannter Farbmodus '{}'. Möglich sind 'always', 'never' oder 'auto'."),
+    ("Unknown coordinates '{}'. They can be 'letters', 'spreadsheet' or 'numeric'.",
+        "Unbekannte Koordinaten '{}'. Möglich sind 'letters', 'spreadsheet' oder 'numeric'."),
+    ("Unknown glyphs '{}'. They can be 'ascii', 'unicode' or 'emoji'.", "Unbekannte Zeichen '{}'. Möglich sind 'ascii', 'unicode' oder 'emoji'."),
+    ("Unknown language '{}'. It can be 'en', 'hu' or 'de'.", "Unbekannte Sprache '{}'. Möglich sind 'en', 'hu' oder 'de'."),
+    ("Unknown option '{}'. The options are '--line', '--tui', '--accessible', '--color=always|never|auto', '--lang=en|hu|de', '--glyphs=ascii|unicode|emoji', '--coords=letters|spreadsheet|numeric' and '--zoom=compact|normal|large'.",
+        "Unbekannte Option '{}'. Die Optionen sind '--line', '--tui', '--accessible', '--color=always|never|auto', '--lang=en|hu|de', '--glyphs=ascii|unicode|emoji', '--coords=letters|spreadsheet|numeric' und '--zoom=compact|normal|large'."),
+    ("Unknown zoom '{}'. It can be 'compact', 'normal' or 'large'.", "Unbekannter Zoom '{}'. Möglich sind 'compact', 'normal' oder 'large'."),
+    ("Win %", "Quote"),
+    ("Without flags you have won {} of {} games, your best time is {}s and best speed is {} 3BV/s",
+        "Ohne Flaggen hast du {} von {} Spielen gewonnen, deine beste Zeit ist {}s und deine beste Geschwindigkeit {} 3BV/s"),
+    ("Won", "Siege"),
+    ("You can use some hints, type {} to reveal a random tile", "Du kannst Tipps nutzen, gib {} ein, um ein zufälliges Feld aufzudecken"),
+    ("You have defused {} mines", "Du hast {} Minen entschärft"),
+    ("You have exploded {} times", "Du bist {} Mal explodiert"),
+    ("You have revealed {} safe tiles", "Du hast {} sichere Felder aufgedeckt"),
+    ("You won without using a single flag (NF)!", "Du hast ohne eine einzige Flagge gewonnen (NF)!"),
+    ("Your aliases:", "Deine Aliase:"),
+    ("Your best speed is {} 3BV/s with {}% efficiency", "Deine beste Geschwindigkeit ist {} 3BV/s bei {}% Effizienz"),
+    ("Your task is to defuse all the mines.", "Deine Aufgabe ist es, alle Minen zu entschärfen."),
+    ("arrows/hjkl: move  HJKL: pan  space: reveal  f: defuse  ?: mark  c: chord  t: theme  +/-: zoom  n: restart  q: quit  :: command",
+        "Pfeile/hjkl: bewegen  HJKL: schieben  Leertaste: aufdecken  f: entschärfen  ?: markieren  c: Akkord  t: Farbschema  +/-: Zoom  n: neu  q: beenden  :: Befehl"),
+    ("column {} is outside this {}-wide board.", "Spalte {} liegt außerhalb dieses {} Spalten breiten Felds."),
+    ("custom {}, {}", "eigene {}, {}"),
+    ("custom {}x{}", "eigene {}x{}"),
+    ("defused", "entschärft"),
+    ("defused mine", "entschärfte Mine"),
+    ("defused, {}", "entschärft, {}"),
+    ("dense", "dicht"),
+    ("empty", "leer"),
+    ("exploded mine", "explodierte Mine"),
+    ("extra large", "riesig"),
+    ("hidden", "verdeckt"),
+    ("hidden with a question mark, {}", "verdeckt mit Fragezeichen, {}"),
+    ("hidden, {}", "verdeckt, {}"),
+    ("large", "groß"),
+    ("medium", "mittel"),
+    ("mine under a question mark", "Mine unter einem Fragezeichen"),
+    ("missed mine", "übersehene Mine"),
+    ("mouse: left reveals, right defuses, middle or both buttons chord", "Maus: links aufdecken, rechts entschärfen, Mitte oder beide Tasten Akkord"),
+    ("no adjacent mines", "keine angrenzenden Minen"),
+    ("no defusers", "keine Entschärfer"),
+    ("no flagged neighbours", "keine markierten Nachbarn"),
+    ("no hidden neighbours", "keine verdeckten Nachbarn"),
+    ("no mines", "keine Minen"),
+    ("no numbers", "keine Zahlen"),
+    ("no tiles", "keine Felder"),
+    ("normal", "normal"),
+    ("number {}", "Zahl {}"),
+    ("question mark", "Fragezeichen"),
+    ("revealed, {}, {}", "aufgedeckt, {}, {}"),
+    ("row {} is outside this {}-tall board.", "Zeile {} liegt außerhalb dieses {} Zeilen hohen Felds."),
+    ("small", "klein"),
+    ("sparse", "dünn"),
+    ("the column letter and the row number", "den Spaltenbuchstaben und die Zeilennummer"),
+    ("the columns start from 1.", "die Spalten beginnen bei 1."),
+    ("the exploded mine", "die explodierte Mine"),
+    ("the row and the column number with a comma", "die Zeilen- und die Spaltennummer mit einem Komma"),
+    ("the row letter and the column number", "den Zeilenbuchstaben und die Spaltennummer"),
+    ("the rows start from 1.", "die Zeilen beginnen bei 1."),
+    ("wrong defuser", "falscher Entschärfer"),
+    ("{} adjacent mines", "{} angrenzende Minen"),
+    ("{} are revealed.", "{} aufgedeckt."),
+    ("{} defused.", "{} entschärft."),
+    ("{} defusers", "{} Entschärfer"),
+    ("{} flagged neighbours", "{} markierte Nachbarn"),
+    ("{} for '{}'", "{} für '{}'"),
+    ("{} hidden neighbours", "{} verdeckte Nachbarn"),
+    ("{} is defused.", "{} ist entschärft."),
+    ("{} is marked with a question mark.", "{} ist mit einem Fragezeichen markiert."),
+    ("{} is {}.", "{} ist {}."),
+    ("{} left: {}.", "{} übrig: {}."),
+    ("{} mines", "{} Minen"),
+    ("{} numbers", "{} Zahlen"),
+    ("{} of {} safe tiles are revealed.", "{} von {} sicheren Feldern sind aufgedeckt."),
+    ("{} on safe tiles: {}.", "{} auf sicheren Feldern: {}."),
+    ("{} on the frontier: {}.", "{} am Rand: {}."),
+    ("{} tiles", "{} Felder"),
+    ("{} to {} {}", "{} bis {} {}"),
+    ("{} was a mine.", "{} war eine Mine."),
+];
+
+#[test]
+fn from_name_test() {
+    assert_eq!(Some(Language::Hungarian), Language::from_name("hu_HU.UTF-8"));
+    assert_eq!(Some(Language::German), Language::from_name("Deutsch"));
+    assert_eq!(None, Language::from_name("C"));
+    let env = |name: &str| match name {
+        "LC_ALL" => Some(String::new()),
+        "LANG" => Some("de_AT.UTF-8".to_string()),
+        _ => None,
+    };
+    assert_eq!(Language::German, Language::from_env(env));
+    assert_eq!(Language::English, Language::from_env(|_| None));
+}
+
+#[test]
+fn fill_test() {
+    assert_eq!("3 of 5", fill("{} of {}", &[&3, &5]));
+    assert_eq!("5-ből 3", fill("{1}-ből {0}", &[&3, &5]));
+    assert_eq!("{} missing", fill("{} missing", &[]));
+    assert_eq!("Already revealed...", translate(Language::English, "Already revealed..."));
+    assert_ne!("Already revealed...", translate(Language::German, "Already revealed..."));
+}
+
+/// The marks of a text, to compare the translations with the English one
+#[cfg(test)]
+fn marks(text: &str) -> Vec<String> {
+    let mut marks: Vec<String> = Vec::new();
+    let mut next = 0;
+    for part in text.split('{').skip(1) {
+        let mark = &part[..part.find('}').unwrap_or(0)];
+        if mark.is_empty() {
+            marks.push(next.to_string());
+            next += 1;
+        } else {
+            marks.push(mark.to_string());
+        }
+    }
+    marks.sort();
+    marks
+}
+
+#[test]
+fn catalog_test() {
+    let sources = [
+        include_str!("gameplay.rs"), include_str!("stats.rs"), include_str!("game.rs"),
+        include_str!("solver.rs"), include_str!("coords.rs"), include_str!("panel.rs"),
+        include_str!("accessible.rs"), include_str!("export.rs"), include_str!("theme.rs"),
+        include_str!("map_draw.rs"), include_str!("tui.rs"), include_str!("viewport.rs"),
+        include_str!("config.rs"), include_str!("../main.rs"),
+    ];
+    for language in [Language::Hungarian, Language::German] {
+        for (english, text) in language.catalog() {
+            // every key is a text of the code, so a changed English text is not left behind
+            let literal = format!("\"{}\"", english.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"));
+            assert!(sources.iter().any(|source| source.contains(&literal)), "{language:?} key not in the code: {english}");
+            assert_eq!(marks(english), marks(text), "{language:?}: {english}");
+        }
+        assert_eq!(HUNGARIAN.len(), GERMAN.len());
+    }
+}
@@ -1,32 +1,200 @@
-use std::io::ErrorKind;
+use std::fmt::Write as _;
+use std::io::{self, ErrorKind, IsTerminal, Write};
+use std::ops::Range;
+use std::sync::{Mutex, OnceLock};
 
-use crossterm::style::{StyledContent, Stylize};
+use crossterm::{
+    cursor::MoveToPreviousLine,
+    queue,
+    style::{StyledContent, Stylize},
+    terminal::{Clear, ClearType},
+};
 
+use super::accessible::{accessible, describe_map};
+use super::coords::coordinate_scheme;
+use super::gameplay::{notify, MoveReport};
+use super::locale::{tr, trf};
 use super::map_generator::TileState;
+use super::panel::with_panel;
+use super::theme::{highlight_tile, paint_tile, Highlight, Look};
+use super::viewport::{minimap, Viewport};
 
 /// Draws a colorful 2D map for minesweeper
+///
+/// If it doesn't fit in the terminal, only a part of it is shown around the explosion.
 pub fn visualize_map(
-    mine_map: &Vec<Vec<TileState>>,
+    mine_map: &[Vec<TileState>],
     mine_char: char,
     show_revealed: bool,
 ) {
-    let mut map: Vec<StyledContent<String>> = Vec::new();
-    map.push(add_first_line(mine_map[0].len() as u8).stylize());
-    map.push("\n".to_string().stylize());
-    for row in 0..(mine_map.len() as u8) {
-        map.append(&mut generate_line(
-            &mine_map[row as usize],
+    if accessible() {
+        println!("{}", describe_map(mine_map, mine_char, show_revealed));
+        return;
+    }
+    let (height, width) = (mine_map.len(), mine_map[0].len());
+    let mut view = Viewport::whole(height, width);
+    view.fit(height, width, 3);
+    if let Some((row, column)) = mine_map.iter().enumerate()
+            .find_map(|(row, line)| line.iter().position(|tile| *tile == TileState::Explosion).map(|column| (row, column))) {
+        view.center(row, column, height, width);
+    }
+    print!("{}", map_text(mine_map, mine_char, show_revealed, None, &view, &[]));
+}
+
+/// The map in the window as text with the colors, every line ends with a new line
+///
+/// The panel is next to the map, and the minimap is under it, when only a part of the map is shown.
+pub fn map_text(
+    mine_map: &[Vec<TileState>],
+    mine_char: char,
+    show_revealed: bool,
+    last_move: Option<&MoveReport>,
+    view: &Viewport,
+    panel: &[String],
+) -> String {
+    let (height, width) = (mine_map.len(), mine_map[0].len());
+    let mut lines = render_window(mine_map, mine_char, show_revealed, last_move, None, view);
+    with_panel(&mut lines, panel, line_width(view.columns, width, height));
+    if !view.is_whole(mine_map.len(), mine_map[0].len()) {
+        lines.extend(minimap(mine_map, view));
+    }
+    let mut text = String::new();
+    for line in lines {
+        for token in line {
+            let _ = write!(text, "{token}");
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Draws the frames of the line mode, each one over the previous on a terminal
+///
+/// Without a terminal the frames are just printed after each other.
+pub struct LineScreen {
+    in_place: bool,
+    lines: usize,
+}
+
+impl LineScreen {
+    pub fn new() -> LineScreen {
+        LineScreen {
+            // a screen reader reads the new lines, redrawing would repeat everything
+            in_place: io::stdin().is_terminal() && io::stdout().is_terminal() && !accessible(),
+            lines: 0,
+        }
+    }
+
+    /// Tells if the frames are drawn over each other
+    pub fn in_place(&self) -> bool {
+        self.in_place
+    }
+
+    /// Draws a frame with one write, over the previous frame if it is still the last thing on the screen
+    pub fn draw(&mut self, frame: &str) -> io::Result<()> {
+        let mut buffer: Vec<u8> = Vec::new();
+        if self.in_place && self.lines > 0 {
+            queue!(buffer, MoveToPreviousLine(self.lines as u16), Clear(ClearType::FromCursorDown))?;
+        }
+        buffer.extend_from_slice(frame.as_bytes());
+        self.lines = frame.lines().count();
+
+        let mut out = io::stdout().lock();
+        out.write_all(&buffer)?;
+        out.flush()
+    }
+
+    /// The player typed a line under the frame, it is overwritten by the next one too
+    pub fn typed_line(&mut self) {
+        self.lines += 1;
+    }
+
+    /// Something else was printed under the frame, the next one starts after it
+    pub fn keep(&mut self) {
+        self.lines = 0;
+    }
+}
+
+/// Builds the lines of the part of the map in the window, the header first
+///
+/// The tile of the last move and the tiles it opened are highlighted, and the tile under the cursor too, if there is any.
+/// Every line is as wide as `line_width`.
+pub fn render_window(
+    mine_map: &[Vec<TileState>],
+    mine_char: char,
+    show_revealed: bool,
+    last_move: Option<&MoveReport>,
+    cursor: Option<(usize, usize)>,
+    view: &Viewport,
+) -> Vec<Vec<StyledContent<String>>> {
+    let scheme = coordinate_scheme();
+    let label_width = left_margin(mine_map.len()) - 1;
+    let header = format!("{:label_width$} {}", "", add_first_line(mine_map[0].len() as u8, view.column_range(), glyphs(), zoom()));
+    let header = format!("{header:<0$}", line_width(view.columns, mine_map[0].len(), mine_map.len()));
+
+    let layout = Layout::new(glyphs(), zoom(), mine_map[0].len());
+    let line_width = line_width(view.columns, mine_map[0].len(), mine_map.len());
+    let rule = |junctions: fn(&Rules) -> [&'static str; 3]| {
+        layout.rules.and_then(|rules| layout.rule(junctions(rules), view.columns))
+            .map(|rule| vec![format!("{:label_width$} {rule:<rest$}", "", rest = line_width - label_width - 1).stylize()])
+    };
+
+    let mut map: Vec<Vec<StyledContent<String>>> = Vec::new();
+    map.push(vec![header.clone().stylize()]);
+    map.extend(rule(|rules| rules.top));
+    for row in view.row_range() {
+        // the name of the row on both sides, so it is easy to follow on wide maps
+        let decorate = |column: usize, token: StyledContent<String>| {
+            let token = match last_move {
+                Some(report) if report.tile == Some((row, column)) => highlight_tile(token, Highlight::Played),
+                Some(report) if report.opened.contains(&(row, column)) => highlight_tile(token, Highlight::Opened),
+                _ => token,
+            };
+            if cursor == Some((row, column)) { token.reverse() } else { token }
+        };
+        let mut line = vec![format!("{:>label_width$} ", scheme.row_name(row)).stylize()];
+        line.extend(generate_line(
+            &mine_map[row],
+            view.column_range(),
             mine_char,
             show_revealed,
+            &decorate,
+            glyphs(),
+            zoom(),
         ));
-        map.push(" ".to_string().stylize());
-        map.push(add_row_number(row).stylize());
-        map.push("\n".to_string().stylize());
+        line.push(" ".to_string().stylize());
+        line.push(format!("{:<label_width$}", scheme.row_name(row)).stylize());
+        map.push(line);
+        if row + 1 < view.row_range().end {
+            map.extend(rule(|rules| rules.inner));
+        }
     }
+    map.extend(rule(|rules| rules.bottom));
+    map.push(vec![header.stylize()]);
+    map
+}
 
-    for token in map {
-        print!("{}", token);
-    }
+/// The screen lines taken by a row of the map, more than one when there are rules between the rows
+pub fn row_lines() -> usize {
+    Layout::new(glyphs(), zoom(), 1).row_lines()
+}
+
+/// The row on a screen line of the map, counted from the first line under the header
+///
+/// `None` on the rules between the rows.
+pub fn row_at(line: usize) -> Option<usize> {
+    Layout::new(glyphs(), zoom(), 1).row_at(line)
+}
+
+/// The width of the row names and the space after them, before the lines of the map
+pub fn left_margin(height: usize) -> usize {
+    coordinate_scheme().row_name(height.saturating_sub(1)).len() + 1
+}
+
+/// How wide a line of the map is on the screen with `columns` tiles shown from a `width` wide map
+pub fn line_width(columns: usize, width: usize, height: usize) -> usize {
+    let layout = Layout::new(glyphs(), zoom(), width);
+    left_margin(height) + layout.separator_width() + layout.before + columns * layout.cell_width() + left_margin(height)
 }
 
 pub fn get_progress(mine_map: &Vec<Vec<TileState>>) -> (usize, usize) {
@@ -35,10 +203,7 @@ pub fn get_progress(mine_map: &Vec<Vec<TileState>>) -> (usize, usize) {
     for row in mine_map {
         visible_tiles += row
             .iter()
-            .filter(|tile| match tile {
-                TileState::VisibleEmpty(_) => true,
-                _ => false,
-            })
+            .filter(|tile| matches!(tile, TileState::VisibleEmpty(_)))
             .count();
         remaining_tiles += row
             .iter()
@@ -59,7 +224,7 @@ const FIRST_ASCII_CHARACTERS: u8 = 65; // letter A
 /// Adds a header at the end of the row as characters
 ///
 /// It starts from 'A' till 'Z' then adds and extra 'A', like 'AA' for row 26, 'AB' for row 27 etc.
-fn add_row_number(row: u8) -> String {
+pub fn add_row_number(row: u8) -> String {
     let mut ret = String::new();
     if row >= MAX_ASCII_CHARACTERS {
         ret.push_str(&add_row_number(row / MAX_ASCII_CHARACTERS - 1));
@@ -75,6 +240,11 @@ fn add_row_number(row: u8) -> String {
     ret
 }
 
+/// The name of a tile the player can type, like "C4"
+pub fn position_name(row: usize, column: usize) -> String {
+    coordinate_scheme().position_name(row, column)
+}
+
 pub fn get_row_number(input: &str) -> Result<u8, ErrorKind> {
     if input.is_empty() || input.len() > 2 {
         return Err(ErrorKind::InvalidInput);
@@ -82,9 +252,8 @@ pub fn get_row_number(input: &str) -> Result<u8, ErrorKind> {
     // max can be IV
     let input = input.to_uppercase();
     if input.len() == 2 {
-        if input.chars().nth(0).unwrap() > 'I' {
-            return Err(ErrorKind::InvalidInput);
-        } else if input.chars().nth(0).unwrap() == 'I' && input.chars().nth(1).unwrap() > 'V' {
+        let (first, second) = (input.chars().next().unwrap(), input.chars().nth(1).unwrap());
+        if first > 'I' || (first == 'I' && second > 'V') {
             return Err(ErrorKind::InvalidInput);
         }
     }
@@ -114,6 +283,13 @@ fn add_row_number_test() {
     assert_eq!("IV", add_row_number(255));
 }
 
+#[test]
+fn position_name_test() {
+    assert_eq!("A1", position_name(0, 0));
+    assert_eq!("C14", position_name(2, 13));
+    assert_eq!("AA3", position_name(26, 2));
+}
+
 #[test]
 fn get_row_number_test() {
     assert_eq!(Ok(0), get_row_number("A"));
@@ -136,119 +312,495 @@ fn get_row_number_test_invalid() {
     assert_eq!(Err(ErrorKind::InvalidInput), get_row_number("A7c"));
 }
 
-fn number_of_spaces(width: u8) -> u8 {
-    let log: u8 = u8::ilog10(width).try_into().unwrap();
-    log
+/// The characters the map is drawn with
+#[derive(Clone, Copy, PartialEq, Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GlyphSet {
+    #[default]
+    Ascii,
+    Unicode,
+    /// Unicode with a bomb for the mines, every tile is two columns wide
+    Emoji,
+}
+
+impl GlyphSet {
+    pub fn from_name(name: &str) -> Option<GlyphSet> {
+        match name {
+            "ascii" => Some(GlyphSet::Ascii),
+            "unicode" => Some(GlyphSet::Unicode),
+            "emoji" => Some(GlyphSet::Emoji),
+            _ => None,
+        }
+    }
+}
+
+static ZOOM_COMMANDS: [&str; 1] = ["zoom"];
+
+/// How big the tiles are drawn
+#[derive(Clone, Copy, PartialEq, Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Zoom {
+    /// One character for every tile, without separators, for the big maps
+    Compact,
+    #[default]
+    Normal,
+    /// Every tile is twice as wide, for presentations
+    Large,
+}
+
+static ALL_ZOOMS: [Zoom; 3] = [Zoom::Compact, Zoom::Normal, Zoom::Large];
+
+impl Zoom {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Zoom::Compact => "compact",
+            Zoom::Normal => "normal",
+            Zoom::Large => "large",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Zoom> {
+        ALL_ZOOMS.iter().copied().find(|zoom| zoom.name() == name.trim().to_lowercase())
+    }
+
+    /// The next bigger zoom, `steps` can be negative for a smaller one, it stops at the ends
+    pub fn step(&self, steps: isize) -> Zoom {
+        let index = ALL_ZOOMS.iter().position(|zoom| zoom == self).unwrap_or(1);
+        ALL_ZOOMS[index.saturating_add_signed(steps).min(ALL_ZOOMS.len() - 1)]
+    }
+}
+
+/// The zoom of the map, it can be changed while playing
+static ZOOM: Mutex<Zoom> = Mutex::new(Zoom::Normal);
+
+pub fn zoom() -> Zoom {
+    *ZOOM.lock().unwrap()
+}
+
+pub fn set_zoom(zoom: Zoom) {
+    *ZOOM.lock().unwrap() = zoom;
+}
+
+/// Handles the 'zoom' command: alone it lists the zooms, with a name or 'in' and 'out' it changes it
+///
+/// Gives back `false` if the input is not a zoom command.
+pub fn zoom_command(input: &str) -> bool {
+    let input = input.trim();
+    let (word, name) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    if !ZOOM_COMMANDS.contains(&&word.to_lowercase()[..]) {
+        return false;
+    }
+    let names: Vec<&str> = ALL_ZOOMS.iter().map(|zoom| zoom.name()).collect();
+    let chosen = match name.trim().to_lowercase().as_str() {
+        "" => {
+            notify(trf("The zoom is '{}'. It can be: {}, or 'zoom in' and 'zoom out'.", &[&zoom().name(), &names.join(", ")]));
+            return true;
+        },
+        "in" | "+" => Some(zoom().step(1)),
+        "out" | "-" => Some(zoom().step(-1)),
+        name => Zoom::from_name(name),
+    };
+    match chosen {
+        Some(chosen) => {
+            set_zoom(chosen);
+            notify(trf("The zoom is '{}' now.", &[&chosen.name()]));
+        },
+        None => notify(trf("There is no zoom called '{}'. Type 'zoom' to see them.", &[&name.trim()])),
+    }
+    true
+}
+
+/// The texts of the tiles, each of them is `width` columns wide on the screen
+#[derive(Clone, Copy)]
+struct Glyphs {
+    width: usize,
+    separator: &'static str,
+    hidden: &'static str,
+    defuser: &'static str,
+    /// A defuser on a safe tile, shown after the game
+    wrong_defuser: &'static str,
+    zero: &'static str,
+    /// `None` uses the character given for the mines, like '*'
+    mine: Option<&'static str>,
+    /// The mines after a won game, `None` uses the character given for the mines, like 'X'
+    won_mine: Option<&'static str>,
+    /// A mine with a question mark on it, shown after a lost game
+    marked_mine: &'static str,
+    /// The mine which ended the game
+    exploded: &'static str,
+    /// The lines between the rows, `None` draws only the separators between the columns
+    rules: Option<&'static Rules>,
+}
+
+/// The lines of a box-drawing grid above, between and under the rows
+struct Rules {
+    line: &'static str,
+    /// The junctions on the left, between the columns and on the right
+    top: [&'static str; 3],
+    inner: [&'static str; 3],
+    bottom: [&'static str; 3],
+}
+
+static BOX_RULES: Rules = Rules { line: "─", top: ["┌", "┬", "┐"], inner: ["├", "┼", "┤"], bottom: ["└", "┴", "┘"] };
+
+static ASCII_GLYPHS: Glyphs = Glyphs {
+    width: 1, separator: "|", hidden: " ", defuser: ".", wrong_defuser: "x", zero: "0",
+    mine: None, won_mine: None, marked_mine: "!", exploded: "@", rules: None,
+};
+static UNICODE_GLYPHS: Glyphs = Glyphs {
+    width: 1, separator: "│", hidden: "▒", defuser: "⚑", wrong_defuser: "✗", zero: "·",
+    mine: Some("✱"), won_mine: Some("⚑"), marked_mine: "!", exploded: "✸", rules: Some(&BOX_RULES),
+};
+static EMOJI_GLYPHS: Glyphs = Glyphs {
+    width: 2, separator: "│", hidden: "▒▒", defuser: "⚑ ", wrong_defuser: "❌", zero: "· ",
+    mine: Some("💣"), won_mine: Some("⚑ "), marked_mine: "❗", exploded: "💥", rules: Some(&BOX_RULES),
+};
+
+static GLYPHS: OnceLock<&'static Glyphs> = OnceLock::new();
+
+/// Chooses the characters of the map, before it is drawn first
+pub fn set_glyphs(set: GlyphSet) {
+    let _ = GLYPHS.set(match set {
+        GlyphSet::Ascii => &ASCII_GLYPHS,
+        GlyphSet::Unicode => &UNICODE_GLYPHS,
+        GlyphSet::Emoji => &EMOJI_GLYPHS,
+    });
+}
+
+fn glyphs() -> &'static Glyphs {
+    GLYPHS.get().copied().unwrap_or(&ASCII_GLYPHS)
+}
+
+impl Glyphs {
+    /// A one column wide text, filled up to the width of the tiles
+    fn narrow(&self, text: &str) -> String {
+        format!("{text:<width$}", width = self.width)
+    }
+
+    fn mine(&self, mine_char: char) -> String {
+        match self.mine {
+            _ if mine_char == ' ' => self.hidden.to_string(),
+            Some(mine) => mine.to_string(),
+            None => mine_char.to_string(),
+        }
+    }
+
+    fn won_mine(&self, mine_char: char) -> String {
+        match self.won_mine {
+            Some(mine) => mine.to_string(),
+            None => self.mine(mine_char),
+        }
+    }
+
+    /// How many columns a tile takes without its separator, the name of the last column fits above it
+    ///
+    /// The header and the lines of the map are both built on it, so they stay aligned.
+    fn tile_width(&self, columns: u8) -> usize {
+        let label = coordinate_scheme().column_name((columns as usize).saturating_sub(1));
+        self.width + label.len() - 1
+    }
+}
+
+/// How a line of the map is made: a separator, then the spaces, the tile and the separator again for every column
+///
+/// The header, the lines and the mouse all follow it, so they stay aligned at every zoom.
+struct Layout {
+    separator: &'static str,
+    /// The lines between the rows, only where the columns have separators too
+    rules: Option<&'static Rules>,
+    /// The spaces before and after the text of a tile
+    before: usize,
+    after: usize,
+    /// The width of the text of a tile
+    tile: usize,
+}
+
+impl Layout {
+    fn new(glyphs: &Glyphs, zoom: Zoom, width: usize) -> Layout {
+        let tile_width = glyphs.tile_width(width as u8);
+        match zoom {
+            Zoom::Compact => Layout { separator: "", rules: None, before: 0, after: 0, tile: glyphs.width },
+            Zoom::Normal => Layout { separator: glyphs.separator, rules: glyphs.rules, before: tile_width - glyphs.width, after: 0, tile: glyphs.width },
+            Zoom::Large => {
+                let spaces = 2 * tile_width + 1 - glyphs.width;
+                Layout { separator: glyphs.separator, rules: glyphs.rules, before: spaces - spaces / 2, after: spaces / 2, tile: glyphs.width }
+            },
+        }
+    }
+
+    fn separator_width(&self) -> usize {
+        self.separator.chars().count()
+    }
+
+    /// The columns of a tile on the screen, with its separator
+    fn cell_width(&self) -> usize {
+        self.before + self.tile + self.after + self.separator_width()
+    }
+
+    /// The screen lines of a row, with the rule under it
+    fn row_lines(&self) -> usize {
+        if self.rules.is_some() { 2 } else { 1 }
+    }
+
+    /// The row on a screen line counted from the first one under the header, or `None` on the rules
+    fn row_at(&self, line: usize) -> Option<usize> {
+        match self.rules {
+            // the top rule is the first line
+            Some(_) => line.checked_sub(1).filter(|line| line % 2 == 0).map(|line| line / 2),
+            None => Some(line),
+        }
+    }
+
+    /// A rule above, between or under the rows, the junctions are under the separators
+    fn rule(&self, junctions: [&str; 3], columns: usize) -> Option<String> {
+        let rules = self.rules?;
+        let mut line = junctions[0].to_string();
+        for column in 0..columns {
+            line.push_str(&rules.line.repeat(self.before + self.tile + self.after));
+            line.push_str(if column + 1 == columns { junctions[2] } else { junctions[1] });
+        }
+        Some(line)
+    }
+
+    /// The tile under a character of a map line, or `None` on the separators
+    fn column_at(&self, x: usize, width: usize) -> Option<usize> {
+        let x = x.checked_sub(self.separator_width())?;
+        let column = x / self.cell_width();
+        if x % self.cell_width() >= self.cell_width() - self.separator_width() || column >= width {
+            None
+        } else {
+            Some(column)
+        }
+    }
 }
 
 /// Generates the first row, aka header to the map
 ///
 /// Empty string in case of 0, and then numbers separated by spaces.
 /// The number of the spaces depends on the maximum column number.
-fn add_first_line(width: u8) -> String {
+/// Compact tiles are too narrow for every name, so only some of them are shown, right above their column.
+fn add_first_line(width: u8, columns: Range<usize>, glyphs: &Glyphs, zoom: Zoom) -> String {
     let mut line = String::new();
     if width == 0 {
         return line;
     }
-    let spaces = number_of_spaces(width) + 1;
-    line.push(' ');
-    for number in 0..width {
-        line.push_str((number + 1).to_string().as_str());
-        let digits = number_of_spaces(number + 1);
-        for _ in 0..(spaces - digits) {
-            line.push(' ');
-        }
+    let layout = Layout::new(glyphs, zoom, width as usize);
+    let scheme = coordinate_scheme();
+    line.push_str(&" ".repeat(layout.separator_width()));
+    match zoom {
+        Zoom::Compact => {
+            for (index, column) in columns.clone().enumerate() {
+                let name = scheme.column_name(column);
+                // the first one and every fifth, when there is room for them
+                let start = index * layout.cell_width();
+                if (column == columns.start || (column + 1) % 5 == 0) && line.len() <= start {
+                    line.push_str(&format!("{name:>0$}", start - line.len() + name.len()));
+                }
+            }
+            let full = columns.len() * layout.cell_width();
+            line.push_str(&" ".repeat(full.saturating_sub(line.len())));
+        },
+        Zoom::Normal => {
+            let cell_width = layout.cell_width();
+            for column in columns {
+                line.push_str(&format!("{:<cell_width$}", scheme.column_name(column)));
+            }
+        },
+        Zoom::Large => {
+            let inner = layout.cell_width() - layout.separator_width();
+            for column in columns {
+                line.push_str(&format!("{:^inner$}{}", scheme.column_name(column), " ".repeat(layout.separator_width())));
+            }
+        },
     }
     line
 }
 
 #[test]
 fn add_first_line_test() {
-    assert_eq!("", add_first_line(0));
-    assert_eq!(" 1 2 3 4 5 ", add_first_line(5));
-    assert_eq!(" 1  2  3  4  5  6  7  8  9  10 ", add_first_line(10));
-    assert_eq!(" 1  2  3  4  5  6  7  8  9  10 11 12 ", add_first_line(12));
+    assert_eq!("", add_first_line(0, 0..0, &ASCII_GLYPHS, Zoom::Normal));
+    assert_eq!(" 1 2 3 4 5 ", add_first_line(5, 0..5, &ASCII_GLYPHS, Zoom::Normal));
+    assert_eq!(" 1  2  3  4  5  6  7  8  9  10 ", add_first_line(10, 0..10, &ASCII_GLYPHS, Zoom::Normal));
+    assert_eq!(" 1  2  3  4  5  6  7  8  9  10 11 12 ", add_first_line(12, 0..12, &ASCII_GLYPHS, Zoom::Normal));
+    assert_eq!(" 1  2  3  ", add_first_line(3, 0..3, &EMOJI_GLYPHS, Zoom::Normal));
+    assert_eq!(" 9  10 11 ", add_first_line(12, 8..11, &ASCII_GLYPHS, Zoom::Normal));
+}
+
+#[test]
+fn zoom_test() {
+    let mine_line = vec![TileState::VisibleEmpty(1); 12];
+    let line = |zoom: Zoom, columns: Range<usize>| -> String {
+        generate_line(&mine_line, columns, ' ', true, &|_, token| token, &ASCII_GLYPHS, zoom).iter().map(|token| token.content().clone()).collect()
+    };
+    assert_eq!("111111111111", line(Zoom::Compact, 0..12));
+    assert_eq!("#1", generate_line(&[TileState::HiddenEmpty(1), TileState::VisibleEmpty(1)], 0..2, ' ', true, &|_, token| token, &ASCII_GLYPHS, Zoom::Compact)
+        .iter().map(|token| token.content().clone()).collect::<String>());
+    assert_eq!("1   5    10 ", add_first_line(12, 0..12, &ASCII_GLYPHS, Zoom::Compact));
+    assert_eq!("8 10 ", add_first_line(12, 7..12, &ASCII_GLYPHS, Zoom::Compact));
+    assert_eq!("|  1  |  1  |  ", line(Zoom::Large, 0..2));
+    assert_eq!("   1     2   ", add_first_line(12, 0..2, &ASCII_GLYPHS, Zoom::Large));
+    assert_eq!("| 1 | 1 | ", generate_line(&mine_line[..2], 0..2, ' ', true, &|_, token| token, &ASCII_GLYPHS, Zoom::Large).iter()
+        .map(|token| token.content().clone()).collect::<String>());
+
+    // the mouse finds the tiles where they are drawn
+    for zoom in ALL_ZOOMS {
+        let layout = Layout::new(&ASCII_GLYPHS, zoom, 12);
+        for (x, ch) in line(zoom, 0..12).chars().enumerate() {
+            match ch {
+                '1' => assert!(layout.column_at(x, 12).is_some(), "{zoom:?} {x}"),
+                '|' => assert_eq!(None, layout.column_at(x, 12), "{zoom:?} {x}"),
+                _ => (),
+            }
+        }
+    }
+    assert_eq!(Some(11), Layout::new(&ASCII_GLYPHS, Zoom::Compact, 12).column_at(11, 12));
+    assert_eq!(None, Layout::new(&ASCII_GLYPHS, Zoom::Compact, 12).column_at(12, 12));
+    assert_eq!(Zoom::Large, Zoom::Normal.step(1));
+    assert_eq!(Zoom::Compact, Zoom::Compact.step(-1));
+}
+
+#[test]
+fn rules_test() {
+    // the junctions are right under the separators, at every width and zoom
+    let mine_line = vec![TileState::VisibleEmpty(1); 12];
+    for (glyphs, zoom, columns) in [(&UNICODE_GLYPHS, Zoom::Normal, 3), (&UNICODE_GLYPHS, Zoom::Large, 12), (&EMOJI_GLYPHS, Zoom::Normal, 12)] {
+        let layout = Layout::new(glyphs, zoom, columns);
+        let line: String = generate_line(&mine_line[..columns], 0..columns, ' ', true, &|_, token| token, glyphs, zoom).iter()
+            .map(|token| token.content().clone())
+            .collect();
+        let rule = layout.rule(BOX_RULES.inner, columns).unwrap();
+        let separators: Vec<usize> = line.chars().enumerate().filter(|(_, ch)| *ch == '│').map(|(x, _)| x).collect();
+        let junctions: Vec<usize> = rule.chars().enumerate().filter(|(_, ch)| "├┼┤".contains(*ch)).map(|(x, _)| x).collect();
+        assert_eq!(separators, junctions, "{zoom:?} {columns}");
+    }
+    assert_eq!(Some("┌─┬─┬─┐".to_string()), Layout::new(&UNICODE_GLYPHS, Zoom::Normal, 3).rule(BOX_RULES.top, 3));
+    assert_eq!(None, Layout::new(&UNICODE_GLYPHS, Zoom::Compact, 3).rule(BOX_RULES.top, 3));
+    assert_eq!(None, Layout::new(&ASCII_GLYPHS, Zoom::Normal, 3).rule(BOX_RULES.top, 3));
+
+    // every row has a rule under it, and the top rule is over the first one
+    let layout = Layout::new(&UNICODE_GLYPHS, Zoom::Normal, 3);
+    assert_eq!((None, Some(0), None, Some(1)), (layout.row_at(0), layout.row_at(1), layout.row_at(2), layout.row_at(3)));
+    assert_eq!(Some(3), Layout::new(&ASCII_GLYPHS, Zoom::Normal, 3).row_at(3));
 }
 
+/// The tile under a character of a map line, or `None` on the separators
+///
+/// It follows the widths of `generate_line`: a separator, the extra spaces and then the tile, for every column.
+pub fn column_at(x: usize, width: usize) -> Option<usize> {
+    if width == 0 {
+        return None;
+    }
+    Layout::new(glyphs(), zoom(), width).column_at(x, width)
+}
+
+/// `decorate` can change the style of a tile, it gets the column of the tile too
 fn generate_line(
-    mine_line: &Vec<TileState>,
+    mine_line: &[TileState],
+    columns: Range<usize>,
     mine_char: char,
     show_revealed: bool,
+    decorate: &dyn Fn(usize, StyledContent<String>) -> StyledContent<String>,
+    glyphs: &Glyphs,
+    zoom: Zoom,
 ) -> Vec<StyledContent<String>> {
     let mut styled_line: Vec<StyledContent<String>> = Vec::new();
 
-    //styled_line.push("blue".blue());
-
-    let spaces = number_of_spaces(mine_line.len() as u8);
-    styled_line.push("|".to_string().stylize());
+    let layout = Layout::new(glyphs, zoom, mine_line.len());
+    // without the separators a blank hidden tile would disappear
+    let compact_glyphs = Glyphs { hidden: "#", ..*glyphs };
+    let glyphs = if zoom == Zoom::Compact && glyphs.hidden.trim().is_empty() { &compact_glyphs } else { glyphs };
+    styled_line.push(layout.separator.to_string().stylize());
     // add spaces
-    let mut spaces_text = String::new();
-    for _ in 0..spaces {
-        spaces_text.push(' ');
-    }
+    let spaces_text = " ".repeat(layout.before);
+    let after_text = " ".repeat(layout.after);
     styled_line.push(spaces_text.clone().stylize());
-    for tile in mine_line.iter() {
-        match tile {
-            TileState::Mine => styled_line.push(
-                if show_revealed {
-                    mine_char.to_string().stylize()
-                } else {
-                    mine_char.to_string().green()
-                }),
-            TileState::Explosion => styled_line.push(mine_char.to_string().on_red()),
-            TileState::Marked(num) => styled_line.push(
-                if mine_char == ' ' {
-                    ".".to_string().stylize()
-                } else {
-                    if *num < 0 {
-                        mine_char.to_string().green()
-                    } else {
-                        ".".to_string().yellow()
-                    }
-                }),
-            TileState::HiddenEmpty(_) => styled_line.push(" ".to_string().stylize()),
-            TileState::VisibleEmpty(num) => {
-                if show_revealed {
-                    if *num == 0 {
-                        styled_line.push(num.to_string().stylize())
-                    } else if *num == 1 {
-                        styled_line.push(num.to_string().blue())
-                    } else if *num == 2 {
-                        styled_line.push(num.to_string().green())
-                    } else if *num == 3 {
-                        styled_line.push(num.to_string().yellow())
-                    } else if *num == 4 {
-                        styled_line.push(num.to_string().red())
-                    } else if *num == 5 {
-                        styled_line.push(num.to_string().magenta())
-                    } else if *num == 6 {
-                        styled_line.push(num.to_string().cyan())
-                    } else if *num == 7 {
-                        styled_line.push(num.to_string().dark_yellow())
-                    } else if *num == 8 {
-                        styled_line.push(num.to_string().dark_red())
-                    }
-                } else {
-                    styled_line.push(" ".to_string().stylize())
-                }
-            }
-            TileState::Question(num) => styled_line.push(
-                if mine_char == ' ' {
-                    "?".to_string().stylize()
-                } else {
-                    if *num < 0 {
-                        mine_char.to_string().stylize()
-                    } else {
-                        "?".to_string().stylize()
-                    }
-                }),
+    for (column, tile) in mine_line.iter().enumerate().skip(columns.start).take(columns.len()) {
+        let token = tile_token(tile, mine_char, show_revealed, glyphs, &paint_tile);
+        styled_line.push(decorate(column, token));
+        if layout.after > 0 {
+            styled_line.push(after_text.clone().stylize());
         }
-        styled_line.push("|".to_string().stylize());
+        styled_line.push(layout.separator.to_string().stylize());
         styled_line.push(spaces_text.clone().stylize());
     }
     styled_line
 }
 
+/// The text of a tile with its style, painted by `paint` for the terminal or for an image
+fn tile_token(
+    tile: &TileState,
+    mine_char: char,
+    show_revealed: bool,
+    glyphs: &Glyphs,
+    paint: &dyn Fn(String, Look) -> StyledContent<String>,
+) -> StyledContent<String> {
+    match tile {
+        TileState::Mine =>
+            if show_revealed {
+                glyphs.mine(mine_char).stylize()
+            } else {
+                paint(glyphs.won_mine(mine_char), Look::Defused)
+            },
+        TileState::Explosion if mine_char == ' ' => paint(glyphs.hidden.to_string(), Look::Explosion),
+        TileState::Explosion => paint(glyphs.exploded.to_string(), Look::Explosion),
+        // after a lost game the right defusers stay, and the wrong ones are crossed out
+        TileState::Marked(num) =>
+            if mine_char == ' ' {
+                glyphs.defuser.to_string().stylize()
+            } else if *num < 0 && show_revealed {
+                paint(glyphs.defuser.to_string(), Look::Defused)
+            } else if *num < 0 {
+                paint(glyphs.won_mine(mine_char), Look::Defused)
+            } else {
+                paint(glyphs.wrong_defuser.to_string(), Look::WrongDefuse)
+            },
+        TileState::HiddenEmpty(_) => glyphs.hidden.to_string().stylize(),
+        TileState::VisibleEmpty(num) => {
+            if !show_revealed {
+                glyphs.narrow(" ").stylize()
+            } else if *num == 0 {
+                paint(glyphs.zero.to_string(), Look::Number(0))
+            } else {
+                paint(glyphs.narrow(&num.to_string()), Look::Number(*num))
+            }
+        }
+        TileState::Question(num) =>
+            if mine_char == ' ' || *num >= 0 {
+                glyphs.narrow("?").stylize()
+            } else if show_revealed {
+                glyphs.marked_mine.to_string().stylize()
+            } else {
+                glyphs.won_mine(mine_char).stylize()
+            },
+    }
+}
+
+/// Explains the markers of the map after a lost game
+pub fn post_game_legend() -> Vec<StyledContent<String>> {
+    let glyphs = glyphs();
+    let entries = [
+        (paint_tile(glyphs.defuser.trim().to_string(), Look::Defused), tr("defused mine")),
+        (paint_tile(glyphs.wrong_defuser.trim().to_string(), Look::WrongDefuse), tr("wrong defuser")),
+        (glyphs.mine('*').stylize(), tr("missed mine")),
+        (glyphs.marked_mine.trim().to_string().stylize(), tr("mine under a question mark")),
+        (paint_tile(glyphs.exploded.trim().to_string(), Look::Explosion), tr("the exploded mine")),
+    ];
+    let last = entries.len() - 1;
+    entries.into_iter().enumerate()
+        .flat_map(|(index, (marker, text))| [marker, format!(" {text}{}", if index < last { ", " } else { "" }).stylize()])
+        .collect()
+}
+
+/// The text of a tile with the glyphs of the game, like it is drawn on the map
+pub fn styled_tile(
+    tile: &TileState,
+    mine_char: char,
+    show_revealed: bool,
+    paint: &dyn Fn(String, Look) -> StyledContent<String>,
+) -> StyledContent<String> {
+    tile_token(tile, mine_char, show_revealed, glyphs(), paint)
+}
+
 pub fn get_column_number(input: &str) -> Result<u8, ErrorKind> {
     match input.parse::<u8>() {
         Ok(num) => {
@@ -267,3 +819,110 @@ fn column_number_test() {
     assert_eq!(Ok(0), get_column_number("1"));
     assert_eq!(Ok(13), get_column_number("14"));
 }
+
+#[test]
+fn render_map_test() {
+    let mine_map = vec![
+        vec![TileState::VisibleEmpty(0), TileState::HiddenEmpty(1)],
+        vec![TileState::Marked(-1), TileState::Question(2)],
+    ];
+    let lines: Vec<String> = render_window(&mine_map, ' ', true, None, None, &Viewport::whole(2, 2)).iter()
+        .map(|line| line.iter().map(|token| token.content().clone()).collect())
+        .collect();
+    assert_eq!(vec!["   1 2   ", "A |0| | A", "B |.|?| B", "   1 2   "], lines);
+
+    let highlighted = render_window(&mine_map, ' ', true, None, Some((1, 1)), &Viewport::whole(2, 2));
+    assert!(highlighted[2][6].style().attributes.has(crossterm::style::Attribute::Reverse));
+    assert!(!highlighted[2][3].style().attributes.has(crossterm::style::Attribute::Reverse));
+
+    let report = MoveReport { safe: true, tile: Some((0, 0)), opened: [(0, 0), (0, 1)].into() };
+    let highlighted = render_window(&mine_map, ' ', true, Some(&report), None, &Viewport::whole(2, 2));
+    assert!(highlighted[1][3].style().attributes.has(crossterm::style::Attribute::Underlined));
+    assert!(!highlighted[1][6].style().attributes.has(crossterm::style::Attribute::Underlined));
+    assert!(!highlighted[2][3].style().attributes.has(crossterm::style::Attribute::Underlined));
+}
+
+#[test]
+fn map_text_test() {
+    let mine_map = vec![vec![TileState::VisibleEmpty(0), TileState::HiddenEmpty(1)]];
+    let text = map_text(&mine_map, ' ', true, None, &Viewport::whole(1, 2), &[]);
+    assert_eq!(3, text.lines().count());
+    assert!(text.lines().nth(1).unwrap().ends_with(" A"));
+}
+
+#[test]
+fn render_window_test() {
+    let mine_map = vec![vec![TileState::VisibleEmpty(1); 12]; 4];
+    let view = Viewport { top: 2, left: 8, rows: 2, columns: 3 };
+    let lines: Vec<String> = render_window(&mine_map, ' ', true, None, None, &view).iter()
+        .map(|line| line.iter().map(|token| token.content().clone()).collect())
+        .collect();
+    assert_eq!(vec!["   9  10 11    ", "C | 1| 1| 1|  C", "D | 1| 1| 1|  D", "   9  10 11    "], lines);
+    assert_eq!(lines[1].len(), line_width(3, 12, 4));
+
+    let text = map_text(&mine_map, ' ', true, None, &view, &[]);
+    assert!(text.contains("Columns 9-11 and rows C-D of the 12x4 map"));
+}
+
+#[test]
+fn column_at_test() {
+    // "|0| | A"
+    assert_eq!(None, column_at(0, 2));
+    assert_eq!(Some(0), column_at(1, 2));
+    assert_eq!(None, column_at(2, 2));
+    assert_eq!(Some(1), column_at(3, 2));
+    assert_eq!(None, column_at(5, 2));
+
+    // "| 1| 2| ... | 1| A", each tile is wider from 10 columns
+    assert_eq!(Some(0), column_at(1, 12));
+    assert_eq!(Some(0), column_at(2, 12));
+    assert_eq!(None, column_at(3, 12));
+    assert_eq!(Some(11), column_at(35, 12));
+    assert_eq!(None, column_at(37, 12));
+
+    // the same positions as the drawn line
+    let line: String = generate_line(&vec![TileState::VisibleEmpty(1); 12], 0..12, ' ', true, &|_, token| token, &ASCII_GLYPHS, Zoom::Normal).iter()
+        .map(|token| token.content().clone())
+        .collect();
+    for (x, ch) in line.chars().enumerate() {
+        match ch {
+            '1' => assert!(column_at(x, 12).is_some()),
+            '|' => assert_eq!(None, column_at(x, 12)),
+            _ => (),
+        }
+    }
+}
+
+#[test]
+fn unicode_line_test() {
+    let mine_line = vec![TileState::VisibleEmpty(0), TileState::HiddenEmpty(1), TileState::Marked(-1), TileState::VisibleEmpty(3)];
+    let line = |glyphs: &Glyphs, mine_char: char| -> String {
+        generate_line(&mine_line, 0..mine_line.len(), mine_char, true, &|_, token| token, glyphs, Zoom::Normal).iter().map(|token| token.content().clone()).collect()
+    };
+    assert_eq!("|0| |.|3|", line(&ASCII_GLYPHS, ' '));
+    assert_eq!("│·│▒│⚑│3│", line(&UNICODE_GLYPHS, ' '));
+    assert_eq!("│· │▒▒│⚑ │3 │", line(&EMOJI_GLYPHS, ' '));
+    assert_eq!("│·│▒│⚑│3│", line(&UNICODE_GLYPHS, '*'));
+    assert_eq!("│· │▒▒│⚑ │3 │", line(&EMOJI_GLYPHS, '*'));
+}
+
+#[test]
+fn post_game_line_test() {
+    let mine_line = vec![TileState::Marked(-1), TileState::Marked(2), TileState::Mine, TileState::Explosion, TileState::Question(-1)];
+    let line: String = generate_line(&mine_line, 0..mine_line.len(), '*', true, &|_, token| token, &ASCII_GLYPHS, Zoom::Normal).iter()
+        .map(|token| token.content().clone())
+        .collect();
+    assert_eq!("|.|x|*|@|!|", line);
+    let legend: String = post_game_legend().iter().map(|token| token.content().clone()).collect();
+    assert_eq!(". defused mine, x wrong defuser, * missed mine, ! mine under a question mark, @ the exploded mine", legend);
+
+    // every glyph set tells them apart, and a won map has no lost mines on it
+    for glyphs in [&ASCII_GLYPHS, &UNICODE_GLYPHS, &EMOJI_GLYPHS] {
+        let tiles: Vec<String> = mine_line.iter().map(|tile| tile_token(tile, '*', true, glyphs, &paint_tile).content().clone()).collect();
+        for (index, tile) in tiles.iter().enumerate() {
+            assert!(!tiles[index + 1..].contains(tile), "{tile} is drawn twice");
+        }
+        let won = tile_token(&TileState::Mine, 'X', false, glyphs, &paint_tile).content().clone();
+        assert!(!tiles[2..].contains(&won), "{won} is drawn for a lost mine too");
+    }
+}
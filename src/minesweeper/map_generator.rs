@@ -1,4 +1,5 @@
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 #[cfg(test)]
 use crate::reveal_tile;
@@ -13,20 +14,20 @@ pub enum TileState {
     Question(i16),
 }
 
-pub fn generate_map(width: u8, height: u8) -> Vec<Vec<TileState>> {
+/// Generates a map, the same seed gives the same map
+pub fn generate_map(width: u8, height: u8, seed: u64) -> Vec<Vec<TileState>> {
     // Fill with mines
-    let generator = || match rand::thread_rng().gen_range(1..=100) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut generator = || match rng.gen_range(1..=100) {
         0..=20 => TileState::Mine,
         _ => TileState::HiddenEmpty(0),
     };
     
-    let mut ret: Vec<Vec<TileState>> = Vec::new();
-    ret.reserve(height as usize);
+    let mut ret: Vec<Vec<TileState>> = Vec::with_capacity(height as usize);
 
     // generate an array with mines
     for _ in 0..height {
-        let mut row: Vec<TileState> = Vec::new();
-        row.reserve(width as usize);
+        let mut row: Vec<TileState> = Vec::with_capacity(width as usize);
         for _ in 0..width {
             row.push(generator());
         }
@@ -37,7 +38,7 @@ pub fn generate_map(width: u8, height: u8) -> Vec<Vec<TileState>> {
 }
 
 /// this will fill the numbers in the map
-fn fill_neighbours(mines: &mut Vec<Vec<TileState>>) {
+fn fill_neighbours(mines: &mut [Vec<TileState>]) {
 
     let height = mines.len();
     if height > 0 {
@@ -65,73 +66,140 @@ fn add_one(tile: &TileState) -> TileState {
 }
 
 /// count the surrounding tiles (if we are not at the edge)
-pub fn count_neigbour_mines(row: usize, column: usize, mines: &mut Vec<Vec<TileState>>, height: usize, width: usize) -> TileState {    
+pub fn count_neigbour_mines(row: usize, column: usize, mines: &mut [Vec<TileState>], height: usize, width: usize) -> TileState {    
     let mut tile = TileState::HiddenEmpty(0);
     
     // top row
     if row > 0 {
         let row_local = row-1;
         // left
-        if column > 0 {
-            if mines[row_local][column-1] == TileState::Mine {
-                tile = add_one(&tile);
-            }
+        if column > 0 && mines[row_local][column-1] == TileState::Mine {
+            tile = add_one(&tile);
         }
         // middle
         if mines[row_local][column] == TileState::Mine {
             tile = add_one(&tile);
         }
         // right
-        if column+1 < width as usize {
-            if mines[row_local][column+1] == TileState::Mine {
-                tile = add_one(&tile);
-            }
+        if column+1 < width && mines[row_local][column+1] == TileState::Mine {
+            tile = add_one(&tile);
         }
     }
 
     // check this row
     // left
-    if column > 0 {
-        if mines[row][column-1] == TileState::Mine {
-            tile = add_one(&tile);
-        }
+    if column > 0 && mines[row][column-1] == TileState::Mine {
+        tile = add_one(&tile);
     }
     // right
-    if column+1 < width as usize {
-        if mines[row][column+1] == TileState::Mine {
-            tile = add_one(&tile);
-        }
+    if column+1 < width && mines[row][column+1] == TileState::Mine {
+        tile = add_one(&tile);
     }
 
     // bottom row
-    if row+1 < height as usize {
+    if row+1 < height {
         let row_local = row+1;
         // left
-        if column > 0 {
-            if mines[row_local][column-1] == TileState::Mine {
-                tile = add_one(&tile);
-            }
+        if column > 0 && mines[row_local][column-1] == TileState::Mine {
+            tile = add_one(&tile);
         }
         // middle
         if mines[row_local][column] == TileState::Mine {
             tile = add_one(&tile);
         }
         // right
-        if column+1 < width as usize {
-            if mines[row_local][column+1] == TileState::Mine {
-                tile = add_one(&tile);
-            }
+        if column+1 < width && mines[row_local][column+1] == TileState::Mine {
+            tile = add_one(&tile);
         }
     }
     tile
 }
 
+/// Gives back the number written on a tile, or `None` if the tile hides a mine
+///
+/// Markers and question marks remember the underlying number, so this works during the game too.
+pub fn tile_number(tile: &TileState) -> Option<u8> {
+    match tile {
+        TileState::Mine | TileState::Explosion => None,
+        TileState::Marked(num) | TileState::Question(num) => if *num < 0 { None } else { Some(*num as u8) },
+        TileState::HiddenEmpty(num) | TileState::VisibleEmpty(num) => Some(*num),
+    }
+}
+
+/// Counts the mines on the map, regardless of markers or explosions
+pub fn count_mines(mines: &[Vec<TileState>]) -> usize {
+    mines.iter()
+        .map(|row| row.iter().filter(|tile| tile_number(tile).is_none()).count())
+        .sum()
+}
+
+/// The positions of the (up to 8) tiles around the given one
+pub fn neighbours(row: usize, column: usize, height: usize, width: usize) -> Vec<(usize, usize)> {
+    let mut ret = Vec::with_capacity(8);
+    for neighbour_row in row.saturating_sub(1)..=(row + 1).min(height.saturating_sub(1)) {
+        for neighbour_column in column.saturating_sub(1)..=(column + 1).min(width.saturating_sub(1)) {
+            if neighbour_row != row || neighbour_column != column {
+                ret.push((neighbour_row, neighbour_column));
+            }
+        }
+    }
+    ret
+}
+
+/// Calculates the 3BV of the map, the minimum number of clicks needed to clear it
+///
+/// Every opening (a connected area of zeros) counts as one click,
+/// and every number tile that doesn't border an opening counts as one more.
+pub fn count_3bv(mines: &[Vec<TileState>]) -> usize {
+    let height = mines.len();
+    if height == 0 {
+        return 0;
+    }
+    let width = mines[0].len();
+    let mut covered = vec![vec![false; width]; height];
+    let mut clicks = 0;
+
+    // openings first, each one uncovers its border too
+    for row in 0..height {
+        for column in 0..width {
+            if covered[row][column] || tile_number(&mines[row][column]) != Some(0) {
+                continue;
+            }
+            clicks += 1;
+            covered[row][column] = true;
+            let mut to_visit = vec![(row, column)];
+            while let Some((current_row, current_column)) = to_visit.pop() {
+                for (next_row, next_column) in neighbours(current_row, current_column, height, width) {
+                    if covered[next_row][next_column] {
+                        continue;
+                    }
+                    covered[next_row][next_column] = true;
+                    if tile_number(&mines[next_row][next_column]) == Some(0) {
+                        to_visit.push((next_row, next_column));
+                    }
+                }
+            }
+        }
+    }
+
+    // then the lonely numbers
+    for row in 0..height {
+        for column in 0..width {
+            if !covered[row][column] && tile_number(&mines[row][column]).is_some() {
+                clicks += 1;
+            }
+        }
+    }
+    clicks
+}
+
 #[test]
 fn generate_small_map() {
-    assert_eq!(0, generate_map(0,0).len());
-    assert_eq!(1, generate_map(0,1).len());
-    assert_eq!(0, generate_map(0,1)[0].len());
-    assert_eq!(2, generate_map(2,1)[0].len());
+    assert_eq!(0, generate_map(0,0,1).len());
+    assert_eq!(1, generate_map(0,1,1).len());
+    assert_eq!(0, generate_map(0,1,1)[0].len());
+    assert_eq!(2, generate_map(2,1,1)[0].len());
+    assert_eq!(generate_map(20,20,42), generate_map(20,20,42));
 }
 
 #[test]
@@ -201,6 +269,49 @@ fn bug_1_fill_neighbours_again() {
     assert_eq!(mine_map[1][7], TileState::HiddenEmpty(4));
 }
 
+#[test]
+fn neighbours_test() {
+    assert_eq!(vec![(0, 1), (1, 0), (1, 1)], neighbours(0, 0, 3, 3));
+    assert_eq!(8, neighbours(1, 1, 3, 3).len());
+    assert_eq!(vec![(1, 1), (1, 2), (2, 1)], neighbours(2, 2, 3, 3));
+    assert!(neighbours(0, 0, 1, 1).is_empty());
+}
+
+#[test]
+fn count_3bv_test() {
+    assert_eq!(0, count_3bv(&Vec::new()));
+
+    // one opening, it reaches every number
+    let mut mine_map = parse_map(
+" |  |  |  
+ |  |  |  
+ |  |  | *");
+    fill_neighbours(&mut mine_map);
+    assert_eq!(1, count_3bv(&mine_map));
+    assert_eq!(1, count_mines(&mine_map));
+
+    // no openings at all, every number is a click
+    let mut mine_map = parse_map(
+" | *|  
+*|  | *
+ | *|  ");
+    fill_neighbours(&mut mine_map);
+    assert_eq!(5, count_3bv(&mine_map));
+
+    // two openings separated by a wall of mines, plus a lonely number
+    let mut mine_map = parse_map(
+" |  | *|  |  
+ |  | *|  |  
+ | *| *|  |  ");
+    fill_neighbours(&mut mine_map);
+    assert_eq!(3, count_3bv(&mine_map));
+
+    // markers don't change the result
+    mine_map[0][2] = TileState::Marked(-1);
+    mine_map[0][0] = TileState::Question(0);
+    assert_eq!(3, count_3bv(&mine_map));
+}
+
 #[cfg(test)]
 fn parse_map(input: &str) -> Vec<Vec<TileState>> {
     let mut mine_map:Vec<Vec<TileState>> = Vec::new();
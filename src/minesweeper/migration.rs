@@ -0,0 +1,153 @@
+use serde_json::{Map, Value};
+
+use super::stats::{rebuild_buckets, GameRecord, GameResult, NoFlagStats, TrainingStats};
+
+/// The version of the stats file written by this game
+pub const STATS_VERSION: u32 = 3;
+
+/// Why a stats file can't be upgraded
+#[derive(PartialEq, Debug)]
+pub enum MigrationError {
+    /// Written by a newer game, with this version, the file is left as it is
+    Newer(u32),
+    /// Not stats at all, or broken
+    Broken(String),
+}
+
+impl From<serde_json::Error> for MigrationError {
+    fn from(err: serde_json::Error) -> MigrationError {
+        MigrationError::Broken(err.to_string())
+    }
+}
+
+/// A step of the upgrade, the games it takes out of the file are put in the second argument
+type Migration = fn(&mut Map<String, Value>, &mut Vec<GameRecord>) -> Result<(), serde_json::Error>;
+
+/// The steps which upgrade a stats file, the one at index `n` turns version `n` into `n + 1`
+static MIGRATIONS: [Migration; STATS_VERSION as usize] = [from_version_0, from_version_1, from_version_2];
+
+/// The version written in a stats file, the files without one are version 0
+pub fn stats_version(stats: &Value) -> u32 {
+    stats.get("version").and_then(Value::as_u64).map_or(0, |version| version as u32)
+}
+
+/// Upgrades a stats file to the current version, one version at a time
+///
+/// Gives back the games taken out of the file too, they go to the history so nothing is lost.
+/// A file from a newer game is not touched, that is an error.
+pub fn migrate_stats(mut stats: Value) -> Result<(Value, Vec<GameRecord>), MigrationError> {
+    let mut moved = Vec::new();
+    loop {
+        let version = stats_version(&stats);
+        if version == STATS_VERSION {
+            return Ok((stats, moved));
+        }
+        let Some(migration) = MIGRATIONS.get(version as usize) else {
+            return Err(MigrationError::Newer(version));
+        };
+        let Some(fields) = stats.as_object_mut() else {
+            return Err(MigrationError::Broken("the stats are not a JSON object".to_string()));
+        };
+        migration(fields, &mut moved)?;
+        fields.insert("version".to_string(), Value::from(version + 1));
+    }
+}
+
+/// Only the counters, nothing was written without a version, but zeros are the safe guess
+fn from_version_0(stats: &mut Map<String, Value>, _moved: &mut Vec<GameRecord>) -> Result<(), serde_json::Error> {
+    for counter in ["defused", "revealed", "exploded"] {
+        stats.entry(counter).or_insert(Value::from(0));
+    }
+    Ok(())
+}
+
+/// Version 1 got the games, the training and the no flag records later, those can be missing,
+/// and the buckets are counted from the games
+fn from_version_1(stats: &mut Map<String, Value>, _moved: &mut Vec<GameRecord>) -> Result<(), serde_json::Error> {
+    stats.entry("training").or_insert(serde_json::to_value(TrainingStats::default())?);
+    stats.entry("no_flag").or_insert(serde_json::to_value(NoFlagStats::default())?);
+    let games = stats.entry("games").or_insert(Value::Array(Vec::new()));
+    if let Some(games) = games.as_array_mut() {
+        for game in games.iter_mut().filter_map(Value::as_object_mut) {
+            game.entry("no_flag").or_insert(Value::Bool(false));
+        }
+    }
+    let games: Vec<GameRecord> = serde_json::from_value(games.clone())?;
+    stats.insert("buckets".to_string(), serde_json::to_value(rebuild_buckets(&games))?);
+    Ok(())
+}
+
+/// Version 2 kept every game and grew with each one, they are moved to the history, only the fastest win stays
+fn from_version_2(stats: &mut Map<String, Value>, moved: &mut Vec<GameRecord>) -> Result<(), serde_json::Error> {
+    let games: Vec<GameRecord> = match stats.remove("games") {
+        Some(games) => serde_json::from_value(games)?,
+        None => Vec::new(),
+    };
+    let best_game = games.iter()
+        .filter(|game| game.result == GameResult::Won)
+        .max_by(|a, b| a.bbbv_per_second().total_cmp(&b.bbbv_per_second()));
+    stats.insert("best_game".to_string(), serde_json::to_value(best_game)?);
+    moved.extend(games);
+    Ok(())
+}
+
+#[cfg(test)]
+fn migrated_fixture(fixture: &str) -> Value {
+    migrate_stats(serde_json::from_str(fixture).unwrap()).unwrap().0
+}
+
+#[test]
+fn migrate_from_version_0_test() {
+    let stats = migrated_fixture(include_str!("../../tests/fixtures/stats/v0.json"));
+    assert_eq!(STATS_VERSION, stats_version(&stats));
+    assert_eq!(Some(4), stats["revealed"].as_u64());
+    assert_eq!(Some(0), stats["exploded"].as_u64());
+    assert!(stats["best_game"].is_null());
+    assert_eq!(Some(0), stats["buckets"].as_array().map(Vec::len));
+}
+
+#[test]
+fn migrate_from_version_1_test() {
+    // the counters of the first release
+    let stats = migrated_fixture(include_str!("../../tests/fixtures/stats/v1-counters.json"));
+    assert_eq!(STATS_VERSION, stats_version(&stats));
+    assert_eq!((Some(12), Some(230), Some(7)), (stats["defused"].as_u64(), stats["revealed"].as_u64(), stats["exploded"].as_u64()));
+    assert_eq!(Some(0), stats["training"]["games"].as_u64());
+    assert_eq!(Some(0), stats["buckets"].as_array().map(Vec::len));
+
+    // with the games, before the no flag mode
+    let stats = migrated_fixture(include_str!("../../tests/fixtures/stats/v1-games.json"));
+    assert_eq!(Some(false), stats["best_game"]["no_flag"].as_bool());
+    assert_eq!(Some(2), stats["buckets"][0]["games"].as_u64());
+    assert_eq!(Some(1), stats["buckets"][0]["wins"].as_u64());
+
+    // with the training and the no flag records, they stay as they were
+    let stats = migrated_fixture(include_str!("../../tests/fixtures/stats/v1-no-flag.json"));
+    assert_eq!(Some(3), stats["training"]["games"].as_u64());
+    assert_eq!(Some(1), stats["no_flag"]["won"].as_u64());
+    assert_eq!(Some(2), stats["buckets"].as_array().map(Vec::len));
+}
+
+#[test]
+fn migrate_from_version_2_test() {
+    let (stats, moved) = migrate_stats(serde_json::from_str(include_str!("../../tests/fixtures/stats/v2.json")).unwrap()).unwrap();
+    assert_eq!(STATS_VERSION, stats_version(&stats));
+    assert!(stats.get("games").is_none());
+    // every game of the file goes to the history
+    assert_eq!(vec![1760000000, 1760000100, 1760000200], moved.iter().map(|game| game.timestamp).collect::<Vec<_>>());
+    assert_eq!(GameResult::Lost, moved[2].result);
+    // the faster of the two wins
+    assert_eq!(Some(4000), stats["best_game"]["duration_ms"].as_u64());
+    assert_eq!(Some(3), stats["buckets"][0]["games"].as_u64());
+}
+
+#[test]
+fn migrate_current_and_newer_test() {
+    let current = include_str!("../../tests/fixtures/stats/v3.json");
+    let stats: Value = serde_json::from_str(current).unwrap();
+    assert_eq!(stats, migrated_fixture(current));
+
+    let newer = serde_json::json!({ "version": STATS_VERSION + 1, "defused": 1, "revealed": 2, "exploded": 3 });
+    assert_eq!(Some(MigrationError::Newer(STATS_VERSION + 1)), migrate_stats(newer).err());
+    assert!(matches!(migrate_stats(serde_json::json!([1, 2])), Err(MigrationError::Broken(_))));
+}
@@ -2,10 +2,45 @@ mod map_draw;
 pub use map_draw::*;
 
 mod map_generator;
-pub use map_generator::*;
 
 mod gameplay;
 pub use gameplay::*;
 
 mod stats;
-pub use stats::*;
\ No newline at end of file
+pub use stats::*;
+
+mod history;
+
+mod migration;
+
+mod solver;
+
+mod config;
+pub use config::*;
+
+mod game;
+pub use game::*;
+
+mod tui;
+pub use tui::*;
+
+mod locale;
+pub use locale::*;
+
+mod theme;
+pub use theme::*;
+
+mod viewport;
+pub use viewport::*;
+
+mod coords;
+pub use coords::*;
+
+mod export;
+pub use export::*;
+
+mod accessible;
+pub use accessible::*;
+
+mod panel;
+pub use panel::*;
@@ -0,0 +1,57 @@
+use crossterm::style::{StyledContent, Stylize};
+
+use super::game::Game;
+use super::gameplay::Difficulty;
+use super::locale::{tr, trf};
+use super::stats::{lifetime_lines, Stats};
+use super::viewport::screen_size;
+
+/// The space between the map and the panel
+const GAP: &str = "   ";
+
+/// The lines of the panel next to the map: this game first, then the totals of every game
+pub fn side_panel(game: &Game, stats: &Stats) -> Vec<String> {
+    let (visible, all) = game.progress();
+    let percent = (visible * 100).checked_div(all).unwrap_or(100);
+    let difficulty = match game.difficulty() {
+        Difficulty::Custom => trf("custom {}x{}", &[&game.mines[0].len(), &game.mines.len()]),
+        difficulty => tr(difficulty.name()).to_string(),
+    };
+    let mut lines = vec![
+        trf("Time: {}s", &[&game.duration().as_secs()]),
+        trf("Mines left: {}", &[&game.mines_left()]),
+        trf("Progress: {}%", &[&percent]),
+        trf("Hints: {}", &[&game.hints()]),
+        trf("Seed: {}", &[&game.seed()]),
+        trf("Difficulty: {}", &[&difficulty]),
+        String::new(),
+    ];
+    lines.extend(lifetime_lines(stats));
+    lines
+}
+
+/// Puts the panel on the right of the lines of the map, which are all `width` wide
+///
+/// The panel is left out when the screen is too narrow for both.
+pub fn with_panel(map: &mut Vec<Vec<StyledContent<String>>>, panel: &[String], width: usize) {
+    let panel_width = panel.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    if screen_size().is_some_and(|(columns, _)| width + GAP.len() + panel_width > columns) {
+        return;
+    }
+    for (index, text) in panel.iter().enumerate() {
+        if index == map.len() {
+            map.push(vec![" ".repeat(width).stylize()]);
+        }
+        if !text.is_empty() {
+            map[index].push(format!("{GAP}{text}").stylize());
+        }
+    }
+}
+
+#[test]
+fn with_panel_test() {
+    let mut map = vec![vec!["|1|".to_string().stylize()]];
+    with_panel(&mut map, &["Hints: 0".to_string(), "Seed: 7".to_string()], 3);
+    let lines: Vec<String> = map.iter().map(|line| line.iter().map(|token| token.content().clone()).collect()).collect();
+    assert_eq!(vec!["|1|   Hints: 0", "      Seed: 7"], lines);
+}
@@ -0,0 +1,378 @@
+use super::locale::{tr, trf};
+use super::map_generator::{count_mines, neighbours, TileState};
+
+#[cfg(test)]
+use super::map_generator::count_neigbour_mines;
+
+/// The biggest group of border tiles we check every combination for
+const MAX_ENUMERATED_TILES: usize = 18;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Knowledge {
+    Revealed,
+    Unknown,
+    Safe,
+    Mine,
+}
+
+/// A revealed number: exactly `mines` of the `tiles` are mines
+struct Constraint {
+    tiles: Vec<(usize, usize)>,
+    mines: usize,
+}
+
+/// What the player could know about the map, only looking at the revealed numbers
+///
+/// Defusers and question marks are ignored, they might be wrong.
+pub struct Analysis {
+    knowledge: Vec<Vec<Knowledge>>,
+    probabilities: Vec<Vec<f64>>,
+}
+
+impl Analysis {
+    pub fn new(mine_map: &[Vec<TileState>]) -> Analysis {
+        let height = mine_map.len();
+        let width = mine_map.first().map_or(0, |row| row.len());
+        let mut knowledge: Vec<Vec<Knowledge>> = mine_map.iter()
+            .map(|row| row.iter().map(|tile| match tile {
+                TileState::VisibleEmpty(_) => Knowledge::Revealed,
+                _ => Knowledge::Unknown,
+            }).collect())
+            .collect();
+
+        deduce(mine_map, &mut knowledge);
+
+        // the chance of a mine on a tile nobody knows anything about
+        let known_mines = knowledge.iter().flatten().filter(|tile| **tile == Knowledge::Mine).count();
+        let unknown = knowledge.iter().flatten().filter(|tile| **tile == Knowledge::Unknown).count();
+        let density = if unknown == 0 {
+            0.0
+        } else {
+            (count_mines(mine_map).saturating_sub(known_mines) as f64 / unknown as f64).clamp(0.01, 0.99)
+        };
+
+        let mut probabilities = vec![vec![0.0; width]; height];
+        for row in 0..height {
+            for column in 0..width {
+                probabilities[row][column] = match knowledge[row][column] {
+                    Knowledge::Mine => 1.0,
+                    Knowledge::Unknown => density,
+                    _ => 0.0,
+                };
+            }
+        }
+        estimate_border(mine_map, &knowledge, density, &mut probabilities);
+
+        Analysis { knowledge, probabilities }
+    }
+
+    /// The tile is a mine for sure, the numbers around it tell it
+    pub fn is_mine(&self, row: usize, column: usize) -> bool {
+        self.knowledge[row][column] == Knowledge::Mine
+    }
+
+    /// Estimated chance that a hidden tile is a mine, between 0 and 1
+    pub fn mine_probability(&self, row: usize, column: usize) -> f64 {
+        self.probabilities[row][column]
+    }
+
+    /// Every hidden tile which is proven to be safe
+    pub fn safe_tiles(&self) -> Vec<(usize, usize)> {
+        let mut ret = Vec::new();
+        for (row, line) in self.knowledge.iter().enumerate() {
+            for (column, tile) in line.iter().enumerate() {
+                if *tile == Knowledge::Safe {
+                    ret.push((row, column));
+                }
+            }
+        }
+        ret
+    }
+
+    /// The lowest chance of a mine among the hidden tiles, 1 if there is none
+    pub fn safest_guess(&self) -> f64 {
+        let mut best: f64 = 1.0;
+        for (row, line) in self.knowledge.iter().enumerate() {
+            for (column, tile) in line.iter().enumerate() {
+                if *tile == Knowledge::Unknown {
+                    best = best.min(self.probabilities[row][column]);
+                }
+            }
+        }
+        best
+    }
+}
+
+/// The revealed numbers with the tiles around them which are not known yet
+fn constraints(mine_map: &[Vec<TileState>], knowledge: &[Vec<Knowledge>]) -> Vec<Constraint> {
+    let height = mine_map.len();
+    let width = mine_map.first().map_or(0, |row| row.len());
+    let mut ret = Vec::new();
+    for (row, line) in mine_map.iter().enumerate() {
+        for (column, tile) in line.iter().enumerate() {
+            let TileState::VisibleEmpty(number) = *tile else {
+                continue;
+            };
+            let mut tiles = Vec::new();
+            let mut found_mines = 0;
+            for (neighbour_row, neighbour_column) in neighbours(row, column, height, width) {
+                match knowledge[neighbour_row][neighbour_column] {
+                    Knowledge::Unknown => tiles.push((neighbour_row, neighbour_column)),
+                    Knowledge::Mine => found_mines += 1,
+                    _ => (),
+                }
+            }
+            if !tiles.is_empty() {
+                ret.push(Constraint { tiles, mines: usize::from(number).saturating_sub(found_mines) });
+            }
+        }
+    }
+    ret
+}
+
+/// Marks every tile as safe or mine which can be proven from the revealed numbers
+fn deduce(mine_map: &[Vec<TileState>], knowledge: &mut [Vec<Knowledge>]) {
+    loop {
+        let constraints = constraints(mine_map, knowledge);
+        let mut found: Vec<((usize, usize), Knowledge)> = Vec::new();
+
+        for constraint in &constraints {
+            if constraint.mines == 0 {
+                found.extend(constraint.tiles.iter().map(|tile| (*tile, Knowledge::Safe)));
+            } else if constraint.mines == constraint.tiles.len() {
+                found.extend(constraint.tiles.iter().map(|tile| (*tile, Knowledge::Mine)));
+            }
+        }
+
+        // when a number's tiles are all around another number, the difference tells something
+        if found.is_empty() {
+            for small in &constraints {
+                for big in &constraints {
+                    if small.tiles.len() >= big.tiles.len()
+                        || big.mines < small.mines
+                        || !small.tiles.iter().all(|tile| big.tiles.contains(tile)) {
+                        continue;
+                    }
+                    let rest: Vec<(usize, usize)> = big.tiles.iter()
+                        .filter(|tile| !small.tiles.contains(tile))
+                        .copied()
+                        .collect();
+                    let rest_mines = big.mines - small.mines;
+                    if rest_mines == 0 {
+                        found.extend(rest.iter().map(|tile| (*tile, Knowledge::Safe)));
+                    } else if rest_mines == rest.len() {
+                        found.extend(rest.iter().map(|tile| (*tile, Knowledge::Mine)));
+                    }
+                }
+            }
+        }
+
+        if found.is_empty() {
+            return;
+        }
+        for ((row, column), state) in found {
+            knowledge[row][column] = state;
+        }
+    }
+}
+
+/// Estimates the chance of a mine for the hidden tiles next to revealed numbers
+///
+/// Small groups of tiles are checked for every possible mine layout,
+/// bigger ones get the worst ratio of the numbers around them.
+fn estimate_border(mine_map: &[Vec<TileState>], knowledge: &[Vec<Knowledge>], density: f64, probabilities: &mut [Vec<f64>]) {
+    let constraints = constraints(mine_map, knowledge);
+
+    // group the constraints which share tiles
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for (index, constraint) in constraints.iter().enumerate() {
+        let (touching, mut separate): (Vec<Vec<usize>>, Vec<Vec<usize>>) = groups.into_iter()
+            .partition(|group| group.iter().any(|other| constraints[*other].tiles.iter().any(|tile| constraint.tiles.contains(tile))));
+        let mut merged: Vec<usize> = touching.into_iter().flatten().collect();
+        merged.push(index);
+        separate.push(merged);
+        groups = separate;
+    }
+
+    // a layout with one more mine is this much more (or less) likely
+    let ratio = density / (1.0 - density);
+    for group in groups {
+        let mut tiles: Vec<(usize, usize)> = Vec::new();
+        for index in &group {
+            for tile in &constraints[*index].tiles {
+                if !tiles.contains(tile) {
+                    tiles.push(*tile);
+                }
+            }
+        }
+
+        if tiles.len() > MAX_ENUMERATED_TILES {
+            for index in &group {
+                let constraint = &constraints[*index];
+                let local = constraint.mines as f64 / constraint.tiles.len() as f64;
+                for (row, column) in &constraint.tiles {
+                    probabilities[*row][*column] = local.max(probabilities[*row][*column]);
+                }
+            }
+            continue;
+        }
+
+        let rules: Vec<(Vec<usize>, usize)> = group.iter()
+            .map(|index| {
+                let constraint = &constraints[*index];
+                let positions = constraint.tiles.iter()
+                    .map(|tile| tiles.iter().position(|other| other == tile).unwrap())
+                    .collect();
+                (positions, constraint.mines)
+            })
+            .collect();
+        let mut mine_weights = vec![0.0; tiles.len()];
+        let mut total_weight = 0.0;
+        let mut layout = Vec::with_capacity(tiles.len());
+        enumerate_layouts(&rules, tiles.len(), ratio, &mut layout, &mut mine_weights, &mut total_weight);
+
+        if total_weight > 0.0 {
+            for (index, (row, column)) in tiles.iter().enumerate() {
+                probabilities[*row][*column] = mine_weights[index] / total_weight;
+            }
+        }
+    }
+}
+
+/// Tries every mine layout of the tiles which fits the numbers
+fn enumerate_layouts(
+    rules: &[(Vec<usize>, usize)],
+    tile_count: usize,
+    ratio: f64,
+    layout: &mut Vec<bool>,
+    mine_weights: &mut [f64],
+    total_weight: &mut f64,
+) {
+    // give up early if a number can't be satisfied anymore
+    for (positions, mines) in rules {
+        let placed = positions.iter().filter(|position| **position < layout.len() && layout[**position]).count();
+        let open = positions.iter().filter(|position| **position >= layout.len()).count();
+        if placed > *mines || placed + open < *mines {
+            return;
+        }
+    }
+
+    if layout.len() == tile_count {
+        let weight = ratio.powi(layout.iter().filter(|mine| **mine).count() as i32);
+        *total_weight += weight;
+        for (index, mine) in layout.iter().enumerate() {
+            if *mine {
+                mine_weights[index] += weight;
+            }
+        }
+        return;
+    }
+
+    for mine in [false, true] {
+        layout.push(mine);
+        enumerate_layouts(rules, tile_count, ratio, layout, mine_weights, total_weight);
+        layout.pop();
+    }
+}
+
+/// How bad the move was that hit a mine
+#[derive(PartialEq, Debug)]
+pub enum FatalMove {
+    /// The revealed numbers proved that tile was a mine
+    ProvableMine,
+    /// There was no safe tile to find, and this was one of the safest guesses
+    ForcedGuess(f64),
+    /// There was a safer option, the chance of a mine is attached
+    BadGamble(f64),
+}
+
+/// Judges the move on a tile, looking at the map right before it was revealed
+pub fn analyse_fatal_move(mine_map: &[Vec<TileState>], row: usize, column: usize) -> FatalMove {
+    let analysis = Analysis::new(mine_map);
+    if analysis.is_mine(row, column) {
+        return FatalMove::ProvableMine;
+    }
+    let probability = analysis.mine_probability(row, column);
+    if analysis.safe_tiles().is_empty() && probability <= analysis.safest_guess() + 0.005 {
+        FatalMove::ForcedGuess(probability)
+    } else {
+        FatalMove::BadGamble(probability)
+    }
+}
+
+impl FatalMove {
+    pub fn describe(&self) -> String {
+        match self {
+            FatalMove::ProvableMine => tr("That tile was provably a mine, the numbers around it give it away.").to_string(),
+            FatalMove::ForcedGuess(probability) =>
+                trf("That was a forced guess, there was no safe tile to find. It had a {}% chance to be a mine.", &[&format!("{:.0}", probability * 100.0)]),
+            FatalMove::BadGamble(probability) =>
+                trf("That was a bad gamble, it had a {}% chance to be a mine and there was a safer option.", &[&format!("{:.0}", probability * 100.0)]),
+        }
+    }
+}
+
+/// Builds a map from text, '*' is a mine, '.' is a hidden tile, 'o' is a revealed one
+#[cfg(test)]
+fn parse_board(input: &str) -> Vec<Vec<TileState>> {
+    let mut mine_map: Vec<Vec<TileState>> = input.lines()
+        .map(|line| line.chars().map(|ch| if ch == '*' { TileState::Mine } else { TileState::HiddenEmpty(0) }).collect())
+        .collect();
+    let height = mine_map.len();
+    let width = mine_map[0].len();
+    for (row, line) in input.lines().enumerate() {
+        for (column, ch) in line.chars().enumerate() {
+            if ch == '*' {
+                continue;
+            }
+            let TileState::HiddenEmpty(number) = count_neigbour_mines(row, column, &mut mine_map, height, width) else {
+                unreachable!();
+            };
+            mine_map[row][column] = if ch == 'o' { TileState::VisibleEmpty(number) } else { TileState::HiddenEmpty(number) };
+        }
+    }
+    mine_map
+}
+
+#[test]
+fn deduce_test() {
+    // the first 1 has only one hidden neighbour, so the second 1 is satisfied
+    let analysis = Analysis::new(&parse_board("o*o.*"));
+    assert!(analysis.is_mine(0, 1));
+    assert!(!analysis.is_mine(0, 4));
+    assert_eq!(vec![(0, 3)], analysis.safe_tiles());
+}
+
+#[test]
+fn subset_deduce_test() {
+    // the corner 1 shares both of its tiles with the next 1, so the rest of that is safe
+    let analysis = Analysis::new(&parse_board(
+"oo..
+*..."));
+    assert_eq!(vec![(0, 2), (1, 2)], analysis.safe_tiles());
+    assert!(!analysis.is_mine(1, 0));
+    assert!(!analysis.is_mine(1, 1));
+}
+
+#[test]
+fn mine_probability_test() {
+    let analysis = Analysis::new(&parse_board(
+"o.
+.*"));
+    for (row, column) in [(0, 1), (1, 0), (1, 1)] {
+        assert!((analysis.mine_probability(row, column) - 1.0 / 3.0).abs() < 0.001);
+    }
+    assert_eq!(0.0, analysis.mine_probability(0, 0));
+}
+
+#[test]
+fn analyse_fatal_move_test() {
+    let mine_map = parse_board("o*o.*");
+    assert_eq!(FatalMove::ProvableMine, analyse_fatal_move(&mine_map, 0, 1));
+    assert!(matches!(analyse_fatal_move(&mine_map, 0, 4), FatalMove::BadGamble(_)));
+
+    let mine_map = parse_board(
+"o.
+.*");
+    assert!(matches!(analyse_fatal_move(&mine_map, 1, 1), FatalMove::ForcedGuess(_)));
+}
@@ -1,97 +1,731 @@
-use std::fs::{self, create_dir_all, File};
-use std::io::Write;
+use std::fs::{self, create_dir_all, File, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use directories::ProjectDirs;
+use serde_json::Value;
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+use super::gameplay::Difficulty;
+use super::history::{append_history, history_file, read_history, HistoryRecord};
+use super::locale::{tr, trf};
+use super::migration::{migrate_stats, stats_version, MigrationError, STATS_VERSION};
+use super::map_generator::{count_3bv, count_mines, TileState};
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
 pub struct Stats {
     version: u32,
     defused: usize,
     revealed: usize,
     exploded: usize,
+    /// The won game with the best speed, every game is kept in the history
+    #[serde(default)]
+    best_game: Option<GameRecord>,
+    #[serde(default)]
+    training: TrainingStats,
+    #[serde(default)]
+    no_flag: NoFlagStats,
+    /// The records of the similar games together, sorted by the bucket
+    #[serde(default)]
+    buckets: Vec<BucketStats>,
+}
+
+/// Training games are counted separately, they would spoil the real numbers
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+pub struct TrainingStats {
+    games: usize,
+    won: usize,
+    revealed: usize,
+    rewinds: usize,
+}
+
+/// Records of the games played without defusers and question marks
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+pub struct NoFlagStats {
+    games: usize,
+    won: usize,
+    best_ms: Option<u64>,
+    best_bbbv_per_second: Option<f64>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub enum GameResult {
+    Won,
+    Lost,
+    Abandoned,
+}
+
+/// The outcome of one game, with the numbers needed to compare it to other games
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct GameRecord {
+    pub timestamp: u64,     // seconds since the unix epoch
+    pub width: u8,
+    pub height: u8,
+    pub mines: usize,
+    pub result: GameResult,
+    pub duration_ms: u64,
+    pub clicks: usize,
+    pub bbbv: usize,
+    #[serde(default)]
+    pub no_flag: bool,  // played in no flag mode, or won without flags
+}
+
+impl GameRecord {
+    pub fn new(mine_map: &[Vec<TileState>], result: GameResult, clicks: usize, duration: Duration) -> GameRecord {
+        GameRecord {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0),
+            width: mine_map.first().map(|row| row.len()).unwrap_or(0) as u8,
+            height: mine_map.len() as u8,
+            mines: count_mines(mine_map),
+            result,
+            duration_ms: duration.as_millis() as u64,
+            clicks,
+            bbbv: count_3bv(mine_map),
+            no_flag: false,
+        }
+    }
+
+    pub fn seconds(&self) -> f64 {
+        self.duration_ms as f64 / 1000.0
+    }
+
+    /// 3BV per second, the speed of the player
+    ///
+    /// Only a won game has a speed and an efficiency, the others didn't clear the 3BV of the whole map.
+    pub fn bbbv_per_second(&self) -> f64 {
+        if self.result != GameResult::Won || self.duration_ms == 0 {
+            0.0
+        } else {
+            self.bbbv as f64 / self.seconds()
+        }
+    }
+
+    /// 3BV per click, 100% means not a single click was wasted
+    pub fn efficiency(&self) -> f64 {
+        if self.result != GameResult::Won || self.clicks == 0 {
+            0.0
+        } else {
+            self.bbbv as f64 / self.clicks as f64
+        }
+    }
 }
 
-const STATS_VERSION: u32 = 1;
+/// How crowded a custom map is with mines
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Density {
+    Sparse,
+    Normal,
+    Dense,
+}
 
-pub fn save_stats(defused: usize, revealed: usize, exploded: bool) {
-    // get previous content, or empty if not existing
-    let mut data = get_stats();
+impl Density {
+    fn of(mines: usize, tiles: usize) -> Density {
+        // the generator puts a mine on about every 5th tile
+        match mines * 100 / tiles.max(1) {
+            0..=14 => Density::Sparse,
+            15..=25 => Density::Normal,
+            _ => Density::Dense,
+        }
+    }
 
-    // convert from old
-    if data.version != STATS_VERSION {
-        data.version = 1;
-        data.defused = 0;
-        data.revealed = 0;
-        data.exploded = 0;
+    fn name(&self) -> &'static str {
+        match self {
+            Density::Sparse => "sparse",
+            Density::Normal => "normal",
+            Density::Dense => "dense",
+        }
     }
-    // increment the numbers
-    data.defused += defused;
-    data.revealed += revealed;
-    if exploded {
-        data.exploded += 1;
+}
+
+/// The games which are compared to each other, the presets and the classes of the custom maps
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bucket {
+    Preset(Difficulty),
+    /// The size is the smallest preset with at least as many tiles, the biggest one for anything bigger
+    Custom { size: Difficulty, density: Density },
+}
+
+impl Bucket {
+    pub fn of(record: &GameRecord) -> Bucket {
+        match Difficulty::of_size(record.width, record.height) {
+            Difficulty::Custom => {
+                let tiles = usize::from(record.width) * usize::from(record.height);
+                Bucket::Custom { size: Difficulty::by_tiles(tiles), density: Density::of(record.mines, tiles) }
+            },
+            preset => Bucket::Preset(preset),
+        }
     }
 
-    // write file
-    if let Some(proj_dirs) = ProjectDirs::from("com", "ChromaticCarrot",  "Minesweeper") {
-        let mut file = File::create(proj_dirs.data_local_dir()
-                .join("stats.json"))
-                .expect("I just created this file.. How can it be not accessible?");
-        let _ = file.write_all(serde_json::to_string(&data).unwrap().as_bytes());
+    pub fn name(&self) -> String {
+        match self {
+            Bucket::Preset(difficulty) => tr(difficulty.name()).to_string(),
+            Bucket::Custom { size, density } => trf("custom {}, {}", &[&tr(size.name()), &tr(density.name())]),
+        }
     }
+}
 
+/// The records of the games in one bucket
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct BucketStats {
+    bucket: Bucket,
+    games: usize,
+    wins: usize,
+    losses: usize,
+    best_ms: Option<u64>,
+    /// The time of all the won games, for the average
+    won_ms: u64,
+    best_bbbv_per_second: Option<f64>,
+    streak: usize,
+    longest_streak: usize,
 }
 
-pub fn get_stats() -> Stats {
-    if let Some(proj_dirs) = ProjectDirs::from("com", "ChromaticCarrot",  "Minesweeper") {
-        if !proj_dirs.data_local_dir().exists() {
-            let result = create_dir_all(proj_dirs.data_local_dir());
-            match result {
-                Ok(_) => (),
-                Err(err) => println!("Error on creating the folder. {}", err),
+impl BucketStats {
+    fn new(bucket: Bucket) -> BucketStats {
+        BucketStats {
+            bucket,
+            games: 0,
+            wins: 0,
+            losses: 0,
+            best_ms: None,
+            won_ms: 0,
+            best_bbbv_per_second: None,
+            streak: 0,
+            longest_streak: 0,
+        }
+    }
+
+    /// Counts a game, an abandoned game breaks the streak too
+    fn add(&mut self, record: &GameRecord) {
+        self.games += 1;
+        match record.result {
+            GameResult::Won => {
+                self.wins += 1;
+                self.won_ms += record.duration_ms;
+                self.best_ms = Some(self.best_ms.map_or(record.duration_ms, |best| best.min(record.duration_ms)));
+                self.best_bbbv_per_second = Some(self.best_bbbv_per_second.map_or(record.bbbv_per_second(), |best| best.max(record.bbbv_per_second())));
+                self.streak += 1;
+                self.longest_streak = self.longest_streak.max(self.streak);
+            },
+            GameResult::Lost => {
+                self.losses += 1;
+                self.streak = 0;
+            },
+            GameResult::Abandoned => self.streak = 0,
+        }
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            self.wins as f64 / self.games as f64
+        }
+    }
+
+    /// The average time of the won games
+    pub fn average_ms(&self) -> Option<u64> {
+        self.won_ms.checked_div(self.wins as u64)
+    }
+}
+
+/// Counts a game in its bucket, the buckets stay sorted
+fn add_to_bucket(buckets: &mut Vec<BucketStats>, record: &GameRecord) {
+    let bucket = Bucket::of(record);
+    let index = match buckets.binary_search_by(|stats| stats.bucket.cmp(&bucket)) {
+        Ok(index) => index,
+        Err(index) => {
+            buckets.insert(index, BucketStats::new(bucket));
+            index
+        },
+    };
+    buckets[index].add(record);
+}
+
+/// Builds the buckets again from the games, in the order they were played
+pub fn rebuild_buckets(games: &[GameRecord]) -> Vec<BucketStats> {
+    let mut buckets = Vec::new();
+    for record in games {
+        add_to_bucket(&mut buckets, record);
+    }
+    buckets
+}
+
+/// The buckets as a table, with a header line, the columns are aligned
+fn bucket_table(buckets: &[BucketStats]) -> Vec<String> {
+    let seconds = |ms: Option<u64>| ms.map_or("-".to_string(), |ms| format!("{:.1}s", ms as f64 / 1000.0));
+    let mut rows = vec![
+        [tr("Size"), tr("Games"), tr("Won"), tr("Lost"), tr("Win %"), tr("Best"), tr("Avg"), "3BV/s", tr("Streak"), tr("Longest")]
+            .map(str::to_string),
+    ];
+    for stats in buckets {
+        rows.push([
+            stats.bucket.name(),
+            stats.games.to_string(),
+            stats.wins.to_string(),
+            stats.losses.to_string(),
+            format!("{:.0}%", stats.win_rate() * 100.0),
+            seconds(stats.best_ms),
+            seconds(stats.average_ms()),
+            stats.best_bbbv_per_second.map_or("-".to_string(), |speed| format!("{speed:.2}")),
+            stats.streak.to_string(),
+            stats.longest_streak.to_string(),
+        ]);
+    }
+
+    let mut widths = [0; 10];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            // the name on the left, the numbers on the right
+            let mut line = format!("{:<1$}", row[0], widths[0]);
+            for (cell, width) in row.iter().zip(widths).skip(1) {
+                line.push_str(&format!("  {cell:>width$}"));
             }
+            line
+        })
+        .collect()
+}
+
+/// Counts a game in the stats, and adds it to the history if it was played
+pub fn save_game(defused: usize, revealed: usize, entry: Option<&HistoryRecord>) -> io::Result<()> {
+    match (data_file("stats.json"), history_file()) {
+        (Some(path), Some(history)) => save_game_files(&path, &history, defused, revealed, entry),
+        _ => Ok(()),
+    }
+}
+
+/// The history gets the game last: a lost stats file is counted again from the history, which must not have this game yet
+fn save_game_files(path: &Path, history: &Path, defused: usize, revealed: usize, entry: Option<&HistoryRecord>) -> io::Result<()> {
+    let saved = update_stats_file(path, history, |data| data.add_game(defused, revealed, entry.map(HistoryRecord::game_record)));
+    if let Some(entry) = entry {
+        if let Err(err) = append_history(history, entry) {
+            println!("{}", trf("Failed to save the game to the history. {}", &[&err]));
         }
+    }
+    saved
+}
+
+impl Stats {
+    /// The stats of the games of the history, when the stats file is lost
+    pub fn from_history(history: &[HistoryRecord]) -> Stats {
+        let mut data = Stats { version: STATS_VERSION, ..Stats::default() };
+        for entry in history {
+            data.add_game(entry.defused, entry.revealed, Some(entry.game_record()));
+        }
+        data
+    }
 
-        if proj_dirs.data_local_dir()
-                .join("stats.json").exists() {
-            let data = fs::read_to_string(proj_dirs.data_local_dir()
-                                    .join("stats.json"))
-                                    .expect("Failed to read an existing file");
-            
-            if let Ok(stats) = serde_json::from_str(&data) {
-                stats
-            } else {
-                // remove the corrupted file, and create a new one
-                Stats {
-                    version: 0,
-                    defused: 0,
-                    revealed: 0,
-                    exploded: 0,
+    fn add_game(&mut self, defused: usize, revealed: usize, record: Option<GameRecord>) {
+        // increment the numbers
+        self.defused += defused;
+        self.revealed += revealed;
+        if let Some(record) = record {
+            if record.result == GameResult::Lost {
+                self.exploded += 1;
+            }
+            if record.no_flag {
+                self.no_flag.games += 1;
+                if record.result == GameResult::Won {
+                    self.no_flag.won += 1;
+                    self.no_flag.best_ms = Some(self.no_flag.best_ms.map_or(record.duration_ms, |best| best.min(record.duration_ms)));
+                    self.no_flag.best_bbbv_per_second = Some(self.no_flag.best_bbbv_per_second.map_or(record.bbbv_per_second(), |best| best.max(record.bbbv_per_second())));
                 }
             }
-        } else {
-            Stats {
-                version: 0,
-                defused: 0,
-                revealed: 0,
-                exploded: 0,
+            add_to_bucket(&mut self.buckets, &record);
+            if record.result == GameResult::Won && self.best_game.as_ref().is_none_or(|best| record.bbbv_per_second() > best.bbbv_per_second()) {
+                self.best_game = Some(record);
             }
         }
-    } else {
-        Stats {
-            version: 0,
-            defused: 0,
-            revealed: 0,
-            exploded: 0,
+    }
+}
+
+/// Counts a finished or abandoned training game
+pub fn save_training_stats(revealed: usize, rewinds: usize, won: bool) -> io::Result<()> {
+    update_stats(|data| {
+        data.training.games += 1;
+        data.training.revealed += revealed;
+        data.training.rewinds += rewinds;
+        if won {
+            data.training.won += 1;
+        }
+    })
+}
+
+/// Changes the saved stats, nothing is saved without a data folder
+fn update_stats(change: impl FnOnce(&mut Stats)) -> io::Result<()> {
+    match (data_file("stats.json"), history_file()) {
+        (Some(path), Some(history)) => update_stats_file(&path, &history, change),
+        _ => Ok(()),
+    }
+}
+
+/// Reads, changes and writes back the stats, under the lock
+///
+/// The stats of a newer game are an error here, so they are not overwritten.
+fn update_stats_file(path: &Path, history: &Path, change: impl FnOnce(&mut Stats)) -> io::Result<()> {
+    let _lock = lock_stats(path)?;
+    let mut data = read_stats(path, history)?;
+    // the old files are already upgraded, only the empty stats get a version here
+    data.version = STATS_VERSION;
+    change(&mut data);
+    write_atomically(path, &serde_json::to_string(&data)?)
+}
+
+/// Copies a stats file of an other version aside, before it is overwritten with the current one
+///
+/// An existing backup of that version is kept, it is the original file.
+fn backup_stats(path: &Path, version: u32) {
+    let backup = path.with_file_name(format!("stats.v{version}.json.bak"));
+    if !backup.exists() {
+        if let Err(err) = fs::copy(path, &backup) {
+            println!("{}", trf("Failed to back up the stats. {}", &[&err]));
+        }
+    }
+}
+
+/// The path of a file in the data folder of the game, next to the stats
+pub fn data_file(name: &str) -> Option<PathBuf> {
+    ProjectDirs::from("com", "ChromaticCarrot",  "Minesweeper").map(|proj_dirs| proj_dirs.data_local_dir().join(name))
+}
+
+/// Locks the stats until the file is dropped, so two running games don't overwrite each other's results
+fn lock_stats(path: &Path) -> io::Result<File> {
+    if let Some(folder) = path.parent() {
+        create_dir_all(folder)?;
+    }
+    let file = OpenOptions::new().create(true).truncate(false).write(true).open(path.with_file_name("stats.lock"))?;
+    file.lock()?;
+    Ok(file)
+}
+
+/// Writes a file through a temporary one, so a crash leaves either the old or the new content
+fn write_atomically(path: &Path, text: &str) -> io::Result<()> {
+    let temp = path.with_extension("tmp");
+    let mut file = File::create(&temp)?;
+    file.write_all(text.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(temp, path)
+}
+
+/// Renames a broken file, so it is kept for a look but not read again
+fn move_aside(path: &Path) -> io::Result<PathBuf> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+    let aside = path.with_extension(format!("broken-{now}.json"));
+    fs::rename(path, &aside)?;
+    Ok(aside)
+}
+
+/// The stats in the file, upgraded to the current version, with the version they had and the games taken out of them
+fn parse_stats(data: &str) -> Result<(Stats, u32, Vec<GameRecord>), MigrationError> {
+    let stats: Value = serde_json::from_str(data)?;
+    let version = stats_version(&stats);
+    let (stats, moved) = migrate_stats(stats)?;
+    Ok((serde_json::from_value(stats)?, version, moved))
+}
+
+/// Adds the games taken out of an old stats file to the history, the ones it has already are skipped
+///
+/// The seed, the hints and the counters of those games were not kept, they are zeros.
+fn move_to_history(history: &Path, games: &[GameRecord]) -> io::Result<()> {
+    let known = read_history(history);
+    for game in games {
+        if !known.iter().any(|entry| entry.timestamp == game.timestamp && entry.duration_ms == game.duration_ms) {
+            append_history(history, &HistoryRecord::new(game, 0, 0, 0, 0))?;
         }
     }
+    Ok(())
+}
+
+/// The saved stats, empty before the first game
+pub fn get_stats() -> io::Result<Stats> {
+    match (data_file("stats.json"), history_file()) {
+        (Some(path), Some(history)) => read_stats(&path, &history),
+        _ => Ok(Stats::default()),
+    }
+}
+
+/// The stats in the file, upgraded to the current version
+///
+/// A file which can't be read as stats is moved aside with a warning, and the stats are counted again from the history.
+/// A file from a newer game is left as it is and gives an error, so it is not overwritten either.
+fn read_stats(path: &Path, history: &Path) -> io::Result<Stats> {
+    if let Some(folder) = path.parent() {
+        create_dir_all(folder)?;
+    }
+    let data = match fs::read_to_string(path) {
+        Ok(data) => data,
+        // the history keeps every game, so the lost stats can be counted again
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Stats::from_history(&read_history(history))),
+        Err(err) => return Err(err),
+    };
 
+    match parse_stats(&data) {
+        Ok((stats, version, moved)) => {
+            if version != STATS_VERSION {
+                backup_stats(path, version);
+                move_to_history(history, &moved)?;
+            }
+            Ok(stats)
+        },
+        Err(MigrationError::Newer(version)) => Err(io::Error::new(ErrorKind::Unsupported,
+            trf("The stats are saved by a newer version of the game (version {} of the file, this game knows {}), they are not changed.",
+                &[&version, &STATS_VERSION]))),
+        Err(MigrationError::Broken(problem)) => {
+            let aside = move_aside(path)?;
+            println!("{}", trf("The stats can't be read ({}), the file is moved to {} and the stats are counted again from the history.",
+                &[&problem, &aside.display()]));
+            Ok(Stats::from_history(&read_history(history)))
+        },
+    }
 }
 
 pub fn print_stats(stat: &Stats) {
-    print!(
-"Stats:
-    You have defused {} mines
-    You have revealed {} safe tiles
-    You have exploded {} times
-Congrats!\n\n", stat.defused, stat.revealed, stat.exploded);
+    println!("{}", tr("Stats:"));
+    println!("    {}", trf("You have defused {} mines", &[&stat.defused]));
+    println!("    {}", trf("You have revealed {} safe tiles", &[&stat.revealed]));
+    println!("    {}", trf("You have exploded {} times", &[&stat.exploded]));
+    if let Some(best) = &stat.best_game {
+        println!("    {}", trf("Your best speed is {} 3BV/s with {}% efficiency",
+            &[&format!("{:.2}", best.bbbv_per_second()), &format!("{:.0}", best.efficiency() * 100.0)]));
+    }
+    if let (Some(best_ms), Some(best_speed)) = (stat.no_flag.best_ms, stat.no_flag.best_bbbv_per_second) {
+        println!("    {}", trf("Without flags you have won {} of {} games, your best time is {}s and best speed is {} 3BV/s",
+            &[&stat.no_flag.won, &stat.no_flag.games, &format!("{:.1}", best_ms as f64 / 1000.0), &format!("{best_speed:.2}")]));
+    }
+    if stat.training.games > 0 {
+        println!("    {}", trf("In training you have played {} games, won {}, and rewound {} explosions",
+            &[&stat.training.games, &stat.training.won, &stat.training.rewinds]));
+    }
+    if !stat.buckets.is_empty() {
+        println!();
+        for line in bucket_table(&stat.buckets) {
+            println!("    {line}");
+        }
+    }
+    println!("{}\n", tr("Congrats!"));
+}
+
+/// The totals of all the games in short lines, for the panel next to the map
+pub fn lifetime_lines(stat: &Stats) -> Vec<String> {
+    let games: usize = stat.buckets.iter().map(|bucket| bucket.games).sum();
+    let won: usize = stat.buckets.iter().map(|bucket| bucket.wins).sum();
+    vec![
+        trf("Games: {}, won {}", &[&games, &won]),
+        trf("Defused: {}", &[&stat.defused]),
+        trf("Revealed: {}", &[&stat.revealed]),
+        trf("Exploded: {}", &[&stat.exploded]),
+    ]
+}
+
+/// The result screen after a game
+pub fn print_game_summary(record: &GameRecord) {
+    println!("{}", trf("Time: {}s", &[&format!("{:.1}", record.seconds())]));
+    if record.result != GameResult::Won {
+        println!("3BV: {}\n", record.bbbv);
+        return;
+    }
+    println!("3BV: {}, 3BV/s: {:.2}", record.bbbv, record.bbbv_per_second());
+    println!("{}\n", trf("Clicks: {}, efficiency: {}%", &[&record.clicks, &format!("{:.0}", record.efficiency() * 100.0)]));
+}
+
+#[test]
+fn game_record_metrics_test() {
+    let record = GameRecord {
+        timestamp: 0,
+        width: 6,
+        height: 5,
+        mines: 6,
+        result: GameResult::Won,
+        duration_ms: 4000,
+        clicks: 10,
+        bbbv: 8,
+        no_flag: false,
+    };
+    assert_eq!(4.0, record.seconds());
+    assert_eq!(2.0, record.bbbv_per_second());
+    assert_eq!(0.8, record.efficiency());
+
+    let unfinished = GameRecord { duration_ms: 0, clicks: 0, ..record };
+    assert_eq!(0.0, unfinished.bbbv_per_second());
+    assert_eq!(0.0, unfinished.efficiency());
+
+    let lost = GameRecord { result: GameResult::Lost, ..record };
+    assert_eq!(0.0, lost.bbbv_per_second());
+    assert_eq!(0.0, lost.efficiency());
+}
+
+#[test]
+fn stats_without_games_test() {
+    let stats: Stats = serde_json::from_str(r#"{"version":1,"defused":3,"revealed":40,"exploded":2}"#).unwrap();
+    assert_eq!(40, stats.revealed);
+    assert!(stats.best_game.is_none());
+    assert_eq!(0, stats.training.games);
+    assert_eq!(vec!["Games: 0, won 0", "Defused: 3", "Revealed: 40", "Exploded: 2"], lifetime_lines(&stats));
+}
+
+#[test]
+fn buckets_test() {
+    let record = |width, height, mines, result, duration_ms| GameRecord {
+        timestamp: 0,
+        width,
+        height,
+        mines,
+        result,
+        duration_ms,
+        clicks: 10,
+        bbbv: 8,
+        no_flag: false,
+    };
+    let games = vec![
+        record(6, 5, 6, GameResult::Won, 4000),
+        record(6, 5, 6, GameResult::Won, 8000),
+        record(6, 5, 6, GameResult::Lost, 1000),
+        record(6, 5, 6, GameResult::Won, 2000),
+        record(35, 30, 210, GameResult::Abandoned, 9000),
+        record(12, 12, 60, GameResult::Won, 5000),
+    ];
+    let buckets = rebuild_buckets(&games);
+    assert_eq!(
+        vec![Bucket::Preset(Difficulty::Small), Bucket::Preset(Difficulty::ExtraLarge), Bucket::Custom { size: Difficulty::Large, density: Density::Dense }],
+        buckets.iter().map(|stats| stats.bucket).collect::<Vec<_>>()
+    );
+
+    let small = &buckets[0];
+    assert_eq!((4, 3, 1), (small.games, small.wins, small.losses));
+    assert_eq!(0.75, small.win_rate());
+    assert_eq!(Some(2000), small.best_ms);
+    assert_eq!(Some(14000 / 3), small.average_ms());
+    assert_eq!(Some(4.0), small.best_bbbv_per_second);
+    assert_eq!((1, 2), (small.streak, small.longest_streak));
+
+    let abandoned = &buckets[1];
+    assert_eq!((1, 0, 0), (abandoned.games, abandoned.wins, abandoned.losses));
+    assert_eq!(None, abandoned.average_ms());
+
+    // the columns line up, whatever the length of the names
+    let table = bucket_table(&buckets);
+    assert_eq!(4, table.len());
+    assert!(table.iter().all(|line| line.chars().count() == table[0].chars().count()));
+    assert_eq!("Size                 Games  Won  Lost  Win %  Best   Avg  3BV/s  Streak  Longest", table[0]);
+    assert_eq!("small                    4    3     1    75%  2.0s  4.7s   4.00       1        2", table[1]);
+}
+
+#[test]
+fn stats_from_history_test() {
+    let game = GameRecord {
+        timestamp: 0,
+        width: 6,
+        height: 5,
+        mines: 6,
+        result: GameResult::Won,
+        duration_ms: 4000,
+        clicks: 10,
+        bbbv: 8,
+        no_flag: true,
+    };
+    let history = vec![
+        HistoryRecord::new(&game, 1, 0, 6, 24),
+        HistoryRecord::new(&GameRecord { result: GameResult::Lost, no_flag: false, ..game.clone() }, 2, 3, 1, 5),
+    ];
+    let stats = Stats::from_history(&history);
+    assert_eq!(STATS_VERSION, stats.version);
+    assert_eq!((7, 29, 1), (stats.defused, stats.revealed, stats.exploded));
+    assert_eq!(Some(2.0), stats.best_game.as_ref().map(GameRecord::bbbv_per_second));
+    assert_eq!((1, Some(4000)), (stats.no_flag.won, stats.no_flag.best_ms));
+    assert_eq!(rebuild_buckets(&history.iter().map(HistoryRecord::game_record).collect::<Vec<_>>()), stats.buckets);
+    assert_eq!(vec!["Games: 2, won 1", "Defused: 7", "Revealed: 29", "Exploded: 1"], lifetime_lines(&stats));
+}
+
+#[test]
+fn parse_stats_test() {
+    let (stats, version, _) = parse_stats(r#"{"version":1,"defused":3,"revealed":40,"exploded":2}"#).unwrap();
+    assert_eq!((1, STATS_VERSION), (version, stats.version));
+    assert_eq!(40, stats.revealed);
+
+    assert!(parse_stats(r#"{"version":2,"defused":3,"#).is_err());
+    assert!(parse_stats("").is_err());
+    assert_eq!(Some(&MigrationError::Newer(STATS_VERSION + 1)), parse_stats(&format!(r#"{{"version":{}}}"#, STATS_VERSION + 1)).err().as_ref());
+    assert!(parse_stats(r#"{"version":2,"defused":"many"}"#).is_err());
+}
+
+#[test]
+fn stats_files_test() {
+    let folder = std::env::temp_dir().join(format!("minesweeper-stats-test-{}", std::process::id()));
+    create_dir_all(&folder).unwrap();
+    let path = folder.join("stats.json");
+
+    write_atomically(&path, "{}").unwrap();
+    write_atomically(&path, "[]").unwrap();
+    assert_eq!("[]", fs::read_to_string(&path).unwrap());
+    assert!(!path.with_extension("tmp").exists());
+
+    let aside = move_aside(&path).unwrap();
+    assert!(!path.exists());
+    assert_eq!("[]", fs::read_to_string(&aside).unwrap());
+
+    // a second game waits for the lock
+    let lock = folder.join("stats.lock");
+    let first = File::create(&lock).unwrap();
+    first.lock().unwrap();
+    assert!(File::open(&lock).unwrap().try_lock().is_err());
+    drop(first);
+    assert!(File::open(&lock).unwrap().try_lock().is_ok());
+
+    // the stats of a newer game stay in place, not even moved aside
+    let newer = format!(r#"{{"version":{},"defused":1}}"#, STATS_VERSION + 1);
+    write_atomically(&path, &newer).unwrap();
+    assert_eq!(Some(ErrorKind::Unsupported), read_stats(&path, &folder.join("history.jsonl")).err().map(|err| err.kind()));
+    assert_eq!(newer, fs::read_to_string(&path).unwrap());
+
+    // the games of an old file go to the history once, even if it is read again before it is saved
+    let history = folder.join("history.jsonl");
+    write_atomically(&path, include_str!("../../tests/fixtures/stats/v2.json")).unwrap();
+    read_stats(&path, &history).unwrap();
+    read_stats(&path, &history).unwrap();
+    assert_eq!(3, read_history(&history).len());
+    assert!(folder.join("stats.v2.json.bak").exists());
+
+    fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn lost_stats_test() {
+    let folder = std::env::temp_dir().join(format!("minesweeper-lost-stats-test-{}", std::process::id()));
+    create_dir_all(&folder).unwrap();
+    let (path, history) = (folder.join("stats.json"), folder.join("history.jsonl"));
+    let game = GameRecord {
+        timestamp: 0,
+        width: 6,
+        height: 5,
+        mines: 6,
+        result: GameResult::Lost,
+        duration_ms: 4000,
+        clicks: 3,
+        bbbv: 8,
+        no_flag: true,
+    };
+    let entry = HistoryRecord::new(&game, 42, 0, 1, 1);
+
+    // the first game, without a stats file
+    save_game_files(&path, &history, 1, 1, Some(&entry)).unwrap();
+    let stats = read_stats(&path, &history).unwrap();
+    assert_eq!((1, 1, 1, 1), (stats.defused, stats.revealed, stats.exploded, stats.no_flag.games));
+    assert_eq!((1, 1), (stats.buckets[0].games, stats.buckets[0].losses));
+    assert_eq!(1, read_history(&history).len());
+
+    // a broken file is counted again from the history, without the game being saved
+    fs::write(&path, "{").unwrap();
+    save_game_files(&path, &history, 1, 1, Some(&entry)).unwrap();
+    let stats = read_stats(&path, &history).unwrap();
+    assert_eq!((2, 2, 2, 2), (stats.defused, stats.revealed, stats.exploded, stats.no_flag.games));
+    assert_eq!((2, 2), (stats.buckets[0].games, stats.buckets[0].losses));
+    assert_eq!(2, read_history(&history).len());
+
+    fs::remove_dir_all(folder).unwrap();
 }
\ No newline at end of file
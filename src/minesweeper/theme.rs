@@ -0,0 +1,414 @@
+use std::env;
+use std::io::{self, IsTerminal};
+use std::sync::{Mutex, OnceLock};
+
+use crossterm::style::{Attribute, Color, Colored, ContentStyle, StyledContent};
+
+use super::gameplay::notify;
+use super::locale::trf;
+
+static THEME_COMMANDS: [&str; 2] = ["theme", "themes"];
+
+/// The color sets of the map
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Theme {
+    Classic,
+    HighContrast,
+    Deuteranopia,
+    Monochrome,
+}
+
+static ALL_THEMES: [Theme; 4] = [Theme::Classic, Theme::HighContrast, Theme::Deuteranopia, Theme::Monochrome];
+
+impl Theme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Classic => "classic",
+            Theme::HighContrast => "high-contrast",
+            Theme::Deuteranopia => "deuteranopia-safe",
+            Theme::Monochrome => "monochrome",
+        }
+    }
+
+    /// Finds the theme by its name, or a shorter form of it, like "contrast" or "mono"
+    pub fn from_name(name: &str) -> Option<Theme> {
+        match name.trim().to_lowercase().as_str() {
+            "classic" | "default" => Some(Theme::Classic),
+            "high-contrast" | "high contrast" | "contrast" => Some(Theme::HighContrast),
+            "deuteranopia-safe" | "deuteranopia" | "colorblind" => Some(Theme::Deuteranopia),
+            "monochrome" | "mono" => Some(Theme::Monochrome),
+            _ => None,
+        }
+    }
+
+    /// The one after this, to step through all of them
+    pub fn next(&self) -> Theme {
+        let index = ALL_THEMES.iter().position(|theme| theme == self).unwrap_or(0);
+        ALL_THEMES[(index + 1) % ALL_THEMES.len()]
+    }
+}
+
+/// How many colors the terminal can show
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ColorDepth {
+    Basic,
+    Ansi256,
+    TrueColor,
+}
+
+fn color_depth(colorterm: Option<&str>, term: Option<&str>) -> ColorDepth {
+    if matches!(colorterm, Some("truecolor") | Some("24bit")) {
+        ColorDepth::TrueColor
+    } else if term.is_some_and(|term| term.contains("256color")) {
+        ColorDepth::Ansi256
+    } else {
+        ColorDepth::Basic
+    }
+}
+
+/// When to use colors, set with `--color=`
+#[derive(Clone, Copy, PartialEq, Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    Always,
+    Never,
+    /// Colors on a terminal, unless `NO_COLOR` is set
+    #[default]
+    Auto,
+}
+
+impl ColorMode {
+    pub fn from_name(name: &str) -> Option<ColorMode> {
+        match name {
+            "always" => Some(ColorMode::Always),
+            "never" => Some(ColorMode::Never),
+            "auto" => Some(ColorMode::Auto),
+            _ => None,
+        }
+    }
+}
+
+/// How much styling gets into the output
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Styling {
+    Full,
+    /// Only bold, underlined and reversed text, for `NO_COLOR` on a terminal
+    NoColors,
+    /// Not a single escape code, for logs and pipes
+    Plain,
+}
+
+fn styling(mode: ColorMode, terminal: bool, no_color: bool) -> Styling {
+    match mode {
+        ColorMode::Always => Styling::Full,
+        ColorMode::Never => Styling::Plain,
+        ColorMode::Auto if !terminal => Styling::Plain,
+        ColorMode::Auto if no_color => Styling::NoColors,
+        ColorMode::Auto => Styling::Full,
+    }
+}
+
+/// What a tile shows, its style comes from the theme
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Look {
+    Number(u8),
+    Explosion,
+    /// A mine found by the player, or shown after winning
+    Defused,
+    /// A defuser on a safe tile
+    WrongDefuse,
+}
+
+/// A color of a theme, with the closest one on a simple terminal
+struct Paint {
+    basic: Color,
+    rgb: (u8, u8, u8),
+}
+
+const fn paint(basic: Color, r: u8, g: u8, b: u8) -> Paint {
+    Paint { basic, rgb: (r, g, b) }
+}
+
+static CLASSIC_NUMBERS: [Paint; 8] = [
+    paint(Color::Blue, 80, 120, 255),
+    paint(Color::Green, 0, 170, 0),
+    paint(Color::Yellow, 230, 200, 0),
+    paint(Color::Red, 230, 40, 40),
+    paint(Color::Magenta, 200, 0, 200),
+    paint(Color::Cyan, 0, 190, 190),
+    paint(Color::DarkYellow, 170, 130, 0),
+    paint(Color::DarkRed, 150, 0, 0),
+];
+
+static HIGH_CONTRAST_NUMBERS: [Paint; 8] = [
+    paint(Color::Cyan, 0, 255, 255),
+    paint(Color::Green, 0, 255, 0),
+    paint(Color::Yellow, 255, 255, 0),
+    paint(Color::Magenta, 255, 0, 255),
+    paint(Color::Red, 255, 60, 60),
+    paint(Color::White, 255, 255, 255),
+    paint(Color::Blue, 100, 150, 255),
+    paint(Color::Grey, 200, 200, 200),
+];
+
+// the Okabe-Ito colors, which stay apart without seeing red and green
+static DEUTERANOPIA_NUMBERS: [Paint; 8] = [
+    paint(Color::Blue, 0, 114, 178),
+    paint(Color::DarkYellow, 230, 159, 0),
+    paint(Color::Cyan, 86, 180, 233),
+    paint(Color::Red, 213, 94, 0),
+    paint(Color::Magenta, 204, 121, 167),
+    paint(Color::DarkCyan, 0, 158, 115),
+    paint(Color::Yellow, 240, 228, 66),
+    paint(Color::Grey, 150, 150, 150),
+];
+
+impl Paint {
+    fn color(&self, depth: ColorDepth) -> Color {
+        let (r, g, b) = self.rgb;
+        match depth {
+            ColorDepth::Basic => self.basic,
+            ColorDepth::Ansi256 => Color::AnsiValue(ansi_256(r, g, b)),
+            ColorDepth::TrueColor => Color::Rgb { r, g, b },
+        }
+    }
+}
+
+/// The closest color of the 6x6x6 cube of the 256 colors
+fn ansi_256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |value: u8| ((value as u16 * 5 + 127) / 255) as u8;
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+/// The style of a look in a theme
+pub fn style_of(look: Look, theme: Theme, depth: ColorDepth) -> ContentStyle {
+    let mut style = ContentStyle::new();
+    let numbers = match theme {
+        Theme::Classic => &CLASSIC_NUMBERS,
+        Theme::HighContrast => &HIGH_CONTRAST_NUMBERS,
+        Theme::Deuteranopia => &DEUTERANOPIA_NUMBERS,
+        Theme::Monochrome => {
+            // no colors, the weight of the text tells the difference
+            match look {
+                Look::Number(0) | Look::Number(1) | Look::Number(2) => (),
+                Look::Number(3) | Look::Number(4) => style.attributes.set(Attribute::Bold),
+                Look::Number(_) => {
+                    style.attributes.set(Attribute::Bold);
+                    style.attributes.set(Attribute::Underlined);
+                },
+                Look::Explosion => style.attributes.set(Attribute::Reverse),
+                Look::Defused => style.attributes.set(Attribute::Bold),
+                Look::WrongDefuse => style.attributes.set(Attribute::Underlined),
+            }
+            return style;
+        },
+    };
+
+    match look {
+        Look::Number(0) => (),
+        Look::Number(num) => style.foreground_color = Some(numbers[(num.min(8) - 1) as usize].color(depth)),
+        Look::Explosion => {
+            style.background_color = Some(match theme {
+                Theme::Deuteranopia => paint(Color::DarkYellow, 213, 94, 0).color(depth),
+                _ => paint(Color::Red, 220, 0, 0).color(depth),
+            });
+        },
+        Look::Defused => {
+            style.foreground_color = Some(match theme {
+                Theme::Deuteranopia => paint(Color::Blue, 0, 114, 178).color(depth),
+                _ => paint(Color::Green, 0, 200, 0).color(depth),
+            });
+        },
+        Look::WrongDefuse => {
+            style.foreground_color = Some(match theme {
+                Theme::Deuteranopia => paint(Color::Yellow, 240, 228, 66).color(depth),
+                _ => paint(Color::Yellow, 230, 200, 0).color(depth),
+            });
+        },
+    }
+    if theme == Theme::HighContrast {
+        style.attributes.set(Attribute::Bold);
+        if look == Look::Explosion {
+            style.foreground_color = Some(Color::White);
+        }
+    }
+    style
+}
+
+/// The theme of the map, it can be changed while playing
+static THEME: Mutex<Theme> = Mutex::new(Theme::Classic);
+static COLOR_DEPTH: OnceLock<ColorDepth> = OnceLock::new();
+static STYLING: OnceLock<Styling> = OnceLock::new();
+
+/// Decides once if the output has colors, before anything is printed
+pub fn set_color_mode(mode: ColorMode) {
+    let no_color = env::var("NO_COLOR").is_ok_and(|value| !value.is_empty());
+    let styling = *STYLING.get_or_init(|| styling(mode, io::stdout().is_terminal(), no_color));
+    // the colors printed by crossterm directly follow it too
+    Colored::set_ansi_color_disabled(styling != Styling::Full);
+}
+
+fn current_styling() -> Styling {
+    *STYLING.get_or_init(|| Styling::Full)
+}
+
+pub fn current_theme() -> Theme {
+    *THEME.lock().unwrap()
+}
+
+pub fn set_theme(theme: Theme) {
+    *THEME.lock().unwrap() = theme;
+}
+
+fn current_color_depth() -> ColorDepth {
+    *COLOR_DEPTH.get_or_init(|| color_depth(env::var("COLORTERM").ok().as_deref(), env::var("TERM").ok().as_deref()))
+}
+
+/// Styles the text of a tile with the current theme
+pub fn paint_tile(text: String, look: Look) -> StyledContent<String> {
+    let style = match current_styling() {
+        Styling::Full => style_of(look, current_theme(), current_color_depth()),
+        Styling::NoColors => style_of(look, Theme::Monochrome, current_color_depth()),
+        Styling::Plain => ContentStyle::new(),
+    };
+    StyledContent::new(style, text)
+}
+
+/// Styles any text, but leaves out what the output can't have
+pub fn paint_text(text: &str, mut style: ContentStyle) -> StyledContent<String> {
+    match current_styling() {
+        Styling::Full => (),
+        Styling::NoColors => {
+            style.foreground_color = None;
+            style.background_color = None;
+        },
+        Styling::Plain => style = ContentStyle::new(),
+    }
+    StyledContent::new(style, text.to_string())
+}
+
+/// Why a tile stands out from the others on the map
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Highlight {
+    /// The tile of the last move
+    Played,
+    /// A tile opened by the last move, next to the played one
+    Opened,
+}
+
+// a dim background, which keeps the colors of the numbers readable
+static OPENED_TINT: Paint = paint(Color::DarkGrey, 58, 58, 78);
+
+/// Adds the look of a highlight to a style, the opened tiles are only tinted when there are colors
+fn highlight_style(highlight: Highlight, mut style: ContentStyle, styling: Styling, depth: ColorDepth) -> ContentStyle {
+    match (styling, highlight) {
+        (Styling::Plain, _) => (),
+        (_, Highlight::Played) => {
+            style.attributes.set(Attribute::Bold);
+            style.attributes.set(Attribute::Underlined);
+        },
+        (Styling::Full, Highlight::Opened) => style.background_color = Some(OPENED_TINT.color(depth)),
+        (Styling::NoColors, Highlight::Opened) => (),
+    }
+    style
+}
+
+/// Highlights a tile of the map
+pub fn highlight_tile(token: StyledContent<String>, highlight: Highlight) -> StyledContent<String> {
+    let style = highlight_style(highlight, *token.style(), current_styling(), current_color_depth());
+    StyledContent::new(style, token.content().clone())
+}
+
+/// Tells if there is someone watching the output, so waiting for them to read it makes sense
+pub fn slow_output() -> bool {
+    io::stdout().is_terminal()
+}
+
+/// Handles the 'theme' command: alone it lists the themes, with a name it switches to that one
+///
+/// Gives back `false` if the input is not a theme command.
+pub fn theme_command(input: &str) -> bool {
+    let input = input.trim();
+    let (word, name) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    if !THEME_COMMANDS.contains(&&word.to_lowercase()[..]) {
+        return false;
+    }
+    if name.trim().is_empty() {
+        let names: Vec<&str> = ALL_THEMES.iter().map(|theme| theme.name()).collect();
+        notify(trf("The theme is '{}'. The themes are: {}.", &[&current_theme().name(), &names.join(", ")]));
+        return true;
+    }
+    match Theme::from_name(name) {
+        Some(theme) => {
+            set_theme(theme);
+            notify(trf("The theme is '{}' now.", &[&theme.name()]));
+        },
+        None => notify(trf("There is no theme called '{}'. Type 'theme' to see them.", &[&name.trim()])),
+    }
+    true
+}
+
+#[test]
+fn from_name_test() {
+    assert_eq!(Some(Theme::HighContrast), Theme::from_name("Contrast"));
+    assert_eq!(Some(Theme::Deuteranopia), Theme::from_name("deuteranopia-safe"));
+    assert_eq!(None, Theme::from_name("pink"));
+    for theme in ALL_THEMES {
+        assert_eq!(Some(theme), Theme::from_name(theme.name()));
+    }
+    assert_eq!(Theme::Classic, Theme::Monochrome.next());
+}
+
+#[test]
+fn color_depth_test() {
+    assert_eq!(ColorDepth::TrueColor, color_depth(Some("truecolor"), Some("xterm")));
+    assert_eq!(ColorDepth::Ansi256, color_depth(None, Some("xterm-256color")));
+    assert_eq!(ColorDepth::Basic, color_depth(None, Some("linux")));
+    assert_eq!(ColorDepth::Basic, color_depth(None, None));
+}
+
+#[test]
+fn styling_test() {
+    assert_eq!(Styling::Full, styling(ColorMode::Auto, true, false));
+    assert_eq!(Styling::NoColors, styling(ColorMode::Auto, true, true));
+    assert_eq!(Styling::Plain, styling(ColorMode::Auto, false, false));
+    assert_eq!(Styling::Full, styling(ColorMode::Always, false, true));
+    assert_eq!(Styling::Plain, styling(ColorMode::Never, true, false));
+}
+
+#[test]
+fn style_of_test() {
+    assert_eq!(Some(Color::Blue), style_of(Look::Number(1), Theme::Classic, ColorDepth::Basic).foreground_color);
+    assert_eq!(Some(Color::Rgb { r: 0, g: 170, b: 0 }), style_of(Look::Number(2), Theme::Classic, ColorDepth::TrueColor).foreground_color);
+    assert_eq!(21, ansi_256(0, 0, 255));
+    assert_eq!(Some(Color::AnsiValue(ansi_256(0, 114, 178))), style_of(Look::Number(1), Theme::Deuteranopia, ColorDepth::Ansi256).foreground_color);
+    assert_eq!(None, style_of(Look::Number(0), Theme::Classic, ColorDepth::Basic).foreground_color);
+
+    // the numbers of a theme are all different
+    for theme in [Theme::Classic, Theme::HighContrast, Theme::Deuteranopia] {
+        for depth in [ColorDepth::Basic, ColorDepth::TrueColor] {
+            let colors: Vec<_> = (1..=8).map(|num| style_of(Look::Number(num), theme, depth).foreground_color).collect();
+            for (i, color) in colors.iter().enumerate() {
+                assert!(!colors[i + 1..].contains(color), "{theme:?} {depth:?} {i}");
+            }
+        }
+    }
+
+    let mono = style_of(Look::Explosion, Theme::Monochrome, ColorDepth::TrueColor);
+    assert_eq!(None, mono.background_color);
+    assert!(mono.attributes.has(Attribute::Reverse));
+    assert!(style_of(Look::Number(5), Theme::Monochrome, ColorDepth::Basic).attributes.has(Attribute::Underlined));
+}
+
+#[test]
+fn highlight_style_test() {
+    let played = highlight_style(Highlight::Played, ContentStyle::new(), Styling::Full, ColorDepth::Basic);
+    assert!(played.attributes.has(Attribute::Bold) && played.attributes.has(Attribute::Underlined));
+    assert_eq!(Some(Color::DarkGrey), highlight_style(Highlight::Opened, ContentStyle::new(), Styling::Full, ColorDepth::Basic).background_color);
+    assert_eq!(None, highlight_style(Highlight::Opened, ContentStyle::new(), Styling::NoColors, ColorDepth::Basic).background_color);
+    assert_eq!(ContentStyle::new(), highlight_style(Highlight::Played, ContentStyle::new(), Styling::Plain, ColorDepth::TrueColor));
+
+    // the color of the number stays
+    let number = style_of(Look::Number(3), Theme::Classic, ColorDepth::Basic);
+    assert_eq!(number.foreground_color, highlight_style(Highlight::Opened, number, Styling::Full, ColorDepth::Basic).foreground_color);
+}
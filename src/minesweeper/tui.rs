@@ -0,0 +1,408 @@
+use std::io::{self, stdout, Stdout, Write};
+use std::panic;
+use std::sync::Once;
+use std::time::Duration;
+
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton, MouseEventKind},
+    queue, execute,
+    style::{Print, PrintStyledContent},
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+use super::accessible::accessible_command;
+use super::coords::coords_command;
+use super::export::export_command;
+use super::game::{Ending, Game};
+use super::gameplay::{capture_messages, take_messages, Command, GameRules};
+use super::locale::{tr, trf};
+use super::map_draw::{column_at, left_margin, line_width, position_name, render_window, row_at, set_zoom, zoom, zoom_command};
+use super::panel::{side_panel, with_panel};
+use super::stats::Stats;
+use super::theme::{current_theme, set_theme, theme_command};
+use super::viewport::{minimap, Viewport};
+
+static KEYS_HELP: &str = "arrows/hjkl: move  HJKL: pan  space: reveal  f: defuse  ?: mark  c: chord  t: theme  +/-: zoom  n: restart  q: quit  :: command";
+static MOUSE_HELP: &str = "mouse: left reveals, right defuses, middle or both buttons chord";
+
+/// The line of the map header, the rows come after it
+const MAP_TOP: u16 = 2;
+/// The lines of the screen besides the map and the messages
+const RESERVED_LINES: usize = MAP_TOP as usize + 3;
+
+/// What a key press means on the full screen
+#[derive(PartialEq, Debug)]
+enum Action {
+    Move(isize, isize),
+    /// Moves the part of the map on the screen by half of it
+    Pan(isize, isize),
+    Play(Command),
+    NextTheme,
+    /// A bigger or a smaller zoom
+    Zoom(isize),
+    /// Opens the line to type a command, like in the lines mode
+    Prompt,
+    /// The typed command, empty if it was cancelled
+    Typed(String),
+    Ending(Ending),
+}
+
+/// Keeps track of the mouse buttons, the moves happen when they are released
+///
+/// Pressing the left and the right button together chords, when the last one is released.
+#[derive(Default)]
+struct MouseButtons {
+    left: bool,
+    right: bool,
+    chord: bool,
+}
+
+impl MouseButtons {
+    fn event(&mut self, kind: MouseEventKind) -> Option<Command> {
+        match kind {
+            MouseEventKind::Down(button) => {
+                match button {
+                    MouseButton::Left => self.left = true,
+                    MouseButton::Right => self.right = true,
+                    MouseButton::Middle => (),
+                }
+                if self.left && self.right {
+                    self.chord = true;
+                }
+                None
+            },
+            MouseEventKind::Up(button) => {
+                match button {
+                    MouseButton::Left => self.left = false,
+                    MouseButton::Right => self.right = false,
+                    MouseButton::Middle => (),
+                }
+                if self.chord {
+                    if self.left || self.right {
+                        return None;
+                    }
+                    self.chord = false;
+                    return Some(Command::Chord);
+                }
+                match button {
+                    MouseButton::Left => Some(Command::Reveal),
+                    MouseButton::Right => Some(Command::Defuse),
+                    MouseButton::Middle => Some(Command::Chord),
+                }
+            },
+            _ => None,
+        }
+    }
+}
+
+/// The tile under the mouse on the screen
+fn tile_at(x: u16, y: u16, view: &Viewport, height: usize, width: usize) -> Option<(usize, usize)> {
+    let row = row_at((y.checked_sub(MAP_TOP + 1)?) as usize)?;
+    if row >= view.rows {
+        return None;
+    }
+    // the row names are on the left
+    column_at((x as usize).checked_sub(left_margin(height))?, width)
+        .filter(|column| *column < view.columns)
+        .map(|column| (view.top + row, view.left + column))
+}
+
+fn key_action(code: KeyCode) -> Option<Action> {
+    match code {
+        KeyCode::Left | KeyCode::Char('h') => Some(Action::Move(0, -1)),
+        KeyCode::Right | KeyCode::Char('l') => Some(Action::Move(0, 1)),
+        KeyCode::Up | KeyCode::Char('k') => Some(Action::Move(-1, 0)),
+        KeyCode::Down | KeyCode::Char('j') => Some(Action::Move(1, 0)),
+        KeyCode::Char('H') => Some(Action::Pan(0, -1)),
+        KeyCode::Char('L') => Some(Action::Pan(0, 1)),
+        KeyCode::Char('K') | KeyCode::PageUp => Some(Action::Pan(-1, 0)),
+        KeyCode::Char('J') | KeyCode::PageDown => Some(Action::Pan(1, 0)),
+        KeyCode::Char(' ') | KeyCode::Enter => Some(Action::Play(Command::Reveal)),
+        KeyCode::Char('f') => Some(Action::Play(Command::Defuse)),
+        KeyCode::Char('?') => Some(Action::Play(Command::Mark)),
+        KeyCode::Char('c') => Some(Action::Play(Command::Chord)),
+        KeyCode::Char('t') => Some(Action::NextTheme),
+        KeyCode::Char('+') => Some(Action::Zoom(1)),
+        KeyCode::Char('-') => Some(Action::Zoom(-1)),
+        KeyCode::Char('n') => Some(Action::Ending(Ending::Restart)),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Action::Ending(Ending::Quit)),
+        KeyCode::Char(':') => Some(Action::Prompt),
+        _ => None,
+    }
+}
+
+/// Types a key into the command line, `Enter` gives the command and `Esc` cancels it
+fn prompt_key(line: &mut String, code: KeyCode) -> Option<Action> {
+    match code {
+        KeyCode::Char(character) => line.push(character),
+        KeyCode::Backspace => {
+            line.pop();
+        },
+        KeyCode::Enter => return Some(Action::Typed(std::mem::take(line))),
+        KeyCode::Esc => return Some(Action::Typed(String::new())),
+        _ => (),
+    }
+    None
+}
+
+/// The commands which can be typed on the full screen besides the moves, like "export html game.html"
+fn typed_command(input: &str, game: &Game) -> bool {
+    export_command(input, game) || theme_command(input) || coords_command(input) || zoom_command(input) || accessible_command(input, &game.mines)
+}
+
+/// Plays the game on the full screen, with a cursor to move around on the map
+///
+/// The terminal is restored before returning, even if drawing failed.
+pub fn play_tui(game: &mut Game, rules: &GameRules, stats: &Stats) -> io::Result<Ending> {
+    let mut out = stdout();
+    restore_on_panic();
+    terminal::enable_raw_mode()?;
+    if let Err(err) = execute!(out, EnterAlternateScreen, Hide, EnableMouseCapture) {
+        let _ = terminal::disable_raw_mode();
+        return Err(err);
+    }
+    capture_messages(true);
+
+    let result = run(game, rules, stats, &mut out);
+
+    capture_messages(false);
+    let restored = execute!(out, DisableMouseCapture, Show, LeaveAlternateScreen);
+    terminal::disable_raw_mode()?;
+    restored?;
+    result
+}
+
+/// Puts the terminal back to normal before the message of a panic, so it can be read
+fn restore_on_panic() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if terminal::is_raw_mode_enabled().unwrap_or(false) {
+                let _ = execute!(stdout(), DisableMouseCapture, Show, LeaveAlternateScreen);
+                let _ = terminal::disable_raw_mode();
+            }
+            previous(info);
+        }));
+    });
+}
+
+fn run(game: &mut Game, rules: &GameRules, stats: &Stats, out: &mut Stdout) -> io::Result<Ending> {
+    let mut cursor = (0, 0);
+    let mut messages: Vec<String> = Vec::new();
+    let mut buttons = MouseButtons::default();
+    let mut prompt: Option<String> = None;
+    let (height, width) = (game.mines.len(), game.mines[0].len());
+    let mut view = Viewport::whole(height, width);
+    while !game.is_won() {
+        view.fit(height, width, RESERVED_LINES + messages.len() + usize::from(prompt.is_some()));
+        view.show(cursor.0, cursor.1);
+        draw(out, game, stats, cursor, &view, &messages, prompt.as_deref())?;
+
+        // the clock of the panel goes on while waiting
+        if !event::poll(Duration::from_secs(1))? {
+            continue;
+        }
+        let action = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match prompt.as_mut() {
+                Some(line) => prompt_key(line, key.code),
+                None => key_action(key.code),
+            },
+            Event::Mouse(mouse) => {
+                let command = buttons.event(mouse.kind);
+                match (command, tile_at(mouse.column, mouse.row, &view, height, width)) {
+                    (Some(command), Some(tile)) => {
+                        cursor = tile;
+                        Some(Action::Play(command))
+                    },
+                    _ => None,
+                }
+            },
+            _ => None,  // resizing just redraws
+        };
+        match action {
+            Some(Action::Move(rows, columns)) => {
+                cursor = move_cursor(cursor, rows, columns, height, width);
+            },
+            Some(Action::Pan(rows, columns)) => {
+                cursor = pan_view(&mut view, cursor, rows, columns, height, width);
+            },
+            Some(Action::Play(command)) => {
+                let guess = format!("{} {}", command.name(), position_name(cursor.0, cursor.1));
+                if !game.play(&guess, rules) {
+                    return Ok(Ending::Lost);
+                }
+                messages = take_messages();
+            },
+            Some(Action::NextTheme) => {
+                let theme = current_theme().next();
+                set_theme(theme);
+                messages = vec![trf("The theme is '{}' now.", &[&theme.name()])];
+            },
+            Some(Action::Zoom(steps)) => {
+                let zoom = zoom().step(steps);
+                set_zoom(zoom);
+                messages = vec![trf("The zoom is '{}' now.", &[&zoom.name()])];
+            },
+            Some(Action::Prompt) => prompt = Some(String::new()),
+            Some(Action::Typed(line)) => {
+                prompt = None;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if !typed_command(&line, game) && !game.play(&line, rules) {
+                    return Ok(Ending::Lost);
+                }
+                messages = take_messages();
+            },
+            Some(Action::Ending(ending)) => return Ok(ending),
+            None => (),
+        }
+    }
+    Ok(Ending::Won)
+}
+
+/// Moves the window by half of it, the cursor moves with the map
+fn pan_view(view: &mut Viewport, cursor: (usize, usize), rows: isize, columns: isize, height: usize, width: usize) -> (usize, usize) {
+    let (top, left) = (view.top, view.left);
+    view.pan(rows * (view.rows / 2).max(1) as isize, columns * (view.columns / 2).max(1) as isize, height, width);
+    move_cursor(cursor, view.top as isize - top as isize, view.left as isize - left as isize, height, width)
+}
+
+/// Moves the cursor, but it stays on the map
+fn move_cursor(cursor: (usize, usize), rows: isize, columns: isize, height: usize, width: usize) -> (usize, usize) {
+    (
+        cursor.0.saturating_add_signed(rows).min(height - 1),
+        cursor.1.saturating_add_signed(columns).min(width - 1),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw(out: &mut Stdout, game: &Game, stats: &Stats, cursor: (usize, usize), view: &Viewport, messages: &[String], prompt: Option<&str>) -> io::Result<()> {
+    let (visible, all) = game.progress();
+    queue!(out, MoveTo(0, 0), Clear(ClearType::CurrentLine), Print(trf("Progress: {}/{}", &[&visible, &all])))?;
+
+    let (height, width) = (game.mines.len(), game.mines[0].len());
+    let mut lines = render_window(&game.mines, ' ', true, game.last_move(), Some(cursor), view);
+    with_panel(&mut lines, &side_panel(game, stats), line_width(view.columns, width, height));
+    if !view.is_whole(game.mines.len(), game.mines[0].len()) {
+        lines.extend(minimap(&game.mines, view));
+    }
+    let mut y = MAP_TOP;
+    for line in lines {
+        queue!(out, MoveTo(0, y), Clear(ClearType::CurrentLine))?;
+        for token in line {
+            queue!(out, PrintStyledContent(token))?;
+        }
+        y += 1;
+    }
+
+    y += 1;
+    queue!(out, MoveTo(0, y), Clear(ClearType::CurrentLine), Print(tr(KEYS_HELP)))?;
+    y += 1;
+    queue!(out, MoveTo(0, y), Clear(ClearType::CurrentLine), Print(tr(MOUSE_HELP)))?;
+    for message in messages {
+        y += 1;
+        queue!(out, MoveTo(0, y), Clear(ClearType::CurrentLine), Print(message))?;
+    }
+    if let Some(line) = prompt {
+        y += 1;
+        queue!(out, MoveTo(0, y), Clear(ClearType::CurrentLine), Print(format!(": {line}")))?;
+    }
+    queue!(out, MoveTo(0, y + 1), Clear(ClearType::FromCursorDown))?;
+    out.flush()
+}
+
+#[test]
+fn key_action_test() {
+    assert_eq!(Some(Action::Move(0, -1)), key_action(KeyCode::Char('h')));
+    assert_eq!(Some(Action::Move(1, 0)), key_action(KeyCode::Down));
+    assert_eq!(Some(Action::Play(Command::Reveal)), key_action(KeyCode::Char(' ')));
+    assert_eq!(Some(Action::Play(Command::Defuse)), key_action(KeyCode::Char('f')));
+    assert_eq!(Some(Action::Play(Command::Chord)), key_action(KeyCode::Char('c')));
+    assert_eq!(Some(Action::NextTheme), key_action(KeyCode::Char('t')));
+    assert_eq!(Some(Action::Zoom(-1)), key_action(KeyCode::Char('-')));
+    assert_eq!(Some(Action::Pan(0, 1)), key_action(KeyCode::Char('L')));
+    assert_eq!(Some(Action::Ending(Ending::Quit)), key_action(KeyCode::Esc));
+    assert_eq!(Some(Action::Prompt), key_action(KeyCode::Char(':')));
+    assert_eq!(None, key_action(KeyCode::Char('x')));
+}
+
+#[test]
+fn prompt_key_test() {
+    let mut line = String::new();
+    for character in "export svgg".chars() {
+        assert_eq!(None, prompt_key(&mut line, KeyCode::Char(character)));
+    }
+    assert_eq!(None, prompt_key(&mut line, KeyCode::Backspace));
+    assert_eq!(Some(Action::Typed("export svg".to_string())), prompt_key(&mut line, KeyCode::Enter));
+    assert!(line.is_empty());
+
+    line.push_str("theme");
+    assert_eq!(Some(Action::Typed(String::new())), prompt_key(&mut line, KeyCode::Esc));
+}
+
+#[test]
+fn pan_view_test() {
+    // scrolled to the bottom right corner of a 20x30 map
+    let mut view = Viewport { top: 10, left: 20, rows: 10, columns: 10 };
+    let cursor = pan_view(&mut view, (15, 25), -1, -1, 20, 30);
+    assert_eq!((5, 15), (view.top, view.left));
+    assert_eq!((10, 20), cursor);
+
+    // at the top left corner the window and the cursor stay
+    let mut view = Viewport { top: 0, left: 0, rows: 10, columns: 10 };
+    assert_eq!((3, 4), pan_view(&mut view, (3, 4), -1, -1, 20, 30));
+    assert_eq!((0, 0), (view.top, view.left));
+}
+
+#[test]
+fn move_cursor_test() {
+    assert_eq!((0, 0), move_cursor((0, 0), -1, -1, 5, 6));
+    assert_eq!((1, 0), move_cursor((0, 0), 1, 0, 5, 6));
+    assert_eq!((4, 5), move_cursor((4, 5), 1, 1, 5, 6));
+}
+
+#[test]
+fn mouse_buttons_test() {
+    let mut buttons = MouseButtons::default();
+    assert_eq!(None, buttons.event(MouseEventKind::Down(MouseButton::Left)));
+    assert_eq!(Some(Command::Reveal), buttons.event(MouseEventKind::Up(MouseButton::Left)));
+    assert_eq!(None, buttons.event(MouseEventKind::Down(MouseButton::Right)));
+    assert_eq!(Some(Command::Defuse), buttons.event(MouseEventKind::Up(MouseButton::Right)));
+    assert_eq!(None, buttons.event(MouseEventKind::Down(MouseButton::Middle)));
+    assert_eq!(Some(Command::Chord), buttons.event(MouseEventKind::Up(MouseButton::Middle)));
+    assert_eq!(None, buttons.event(MouseEventKind::Moved));
+
+    // both buttons, released in any order
+    assert_eq!(None, buttons.event(MouseEventKind::Down(MouseButton::Right)));
+    assert_eq!(None, buttons.event(MouseEventKind::Down(MouseButton::Left)));
+    assert_eq!(None, buttons.event(MouseEventKind::Up(MouseButton::Right)));
+    assert_eq!(Some(Command::Chord), buttons.event(MouseEventKind::Up(MouseButton::Left)));
+    assert_eq!(None, buttons.event(MouseEventKind::Down(MouseButton::Left)));
+    assert_eq!(Some(Command::Reveal), buttons.event(MouseEventKind::Up(MouseButton::Left)));
+
+    // the middle button chords after a right click too
+    assert_eq!(None, buttons.event(MouseEventKind::Down(MouseButton::Right)));
+    assert_eq!(Some(Command::Defuse), buttons.event(MouseEventKind::Up(MouseButton::Right)));
+    assert_eq!(Some(Command::Chord), buttons.event(MouseEventKind::Up(MouseButton::Middle)));
+}
+
+#[test]
+fn tile_at_test() {
+    // the header is on MAP_TOP, the first row is under it, after its name
+    let view = Viewport::whole(5, 6);
+    assert_eq!(None, tile_at(3, MAP_TOP, &view, 5, 6));
+    assert_eq!(Some((0, 0)), tile_at(3, MAP_TOP + 1, &view, 5, 6));
+    assert_eq!(Some((4, 5)), tile_at(13, MAP_TOP + 5, &view, 5, 6));
+    assert_eq!(None, tile_at(13, MAP_TOP + 6, &view, 5, 6));
+    assert_eq!(None, tile_at(4, MAP_TOP + 1, &view, 5, 6));
+    assert_eq!(None, tile_at(0, MAP_TOP + 1, &view, 5, 6));
+
+    // the window starts from C3
+    let view = Viewport { top: 2, left: 2, rows: 2, columns: 3 };
+    assert_eq!(Some((2, 2)), tile_at(3, MAP_TOP + 1, &view, 5, 6));
+    assert_eq!(None, tile_at(3, MAP_TOP + 3, &view, 5, 6));
+    assert_eq!(None, tile_at(9, MAP_TOP + 1, &view, 5, 6));
+}
@@ -0,0 +1,242 @@
+use std::io::{self, IsTerminal};
+use std::ops::Range;
+
+use crossterm::{
+    style::{ContentStyle, StyledContent, Stylize},
+    terminal,
+};
+
+use super::gameplay::{notify, parse_index};
+use super::locale::{tr, trf};
+use super::coords::coordinate_scheme;
+use super::map_draw::{line_width, row_lines};
+use super::map_generator::TileState;
+use super::theme::paint_text;
+
+static PAN_COMMANDS: [&str; 2] = ["pan", "view"];
+
+const MINIMAP_COLUMNS: usize = 24;
+const MINIMAP_ROWS: usize = 6;
+
+/// The part of the map on the screen, when all of it doesn't fit
+#[derive(Clone, PartialEq, Debug)]
+pub struct Viewport {
+    pub top: usize,
+    pub left: usize,
+    pub rows: usize,
+    pub columns: usize,
+}
+
+/// The columns and rows of the terminal, or `None` if the output is not a terminal
+pub fn screen_size() -> Option<(usize, usize)> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    terminal::size().ok().map(|(columns, rows)| (columns as usize, rows as usize))
+}
+
+impl Viewport {
+    /// All of the map
+    pub fn whole(height: usize, width: usize) -> Viewport {
+        Viewport { top: 0, left: 0, rows: height, columns: width }
+    }
+
+    pub fn row_range(&self) -> Range<usize> {
+        self.top..self.top + self.rows
+    }
+
+    pub fn column_range(&self) -> Range<usize> {
+        self.left..self.left + self.columns
+    }
+
+    pub fn is_whole(&self, height: usize, width: usize) -> bool {
+        self.rows >= height && self.columns >= width
+    }
+
+    /// Sizes the window to the terminal, leaving `reserved` lines for the rest of the frame
+    ///
+    /// Without a terminal it is the whole map.
+    pub fn fit(&mut self, height: usize, width: usize, reserved: usize) {
+        match screen_size() {
+            Some(screen) => self.fit_to(height, width, screen, reserved),
+            None => *self = Viewport::whole(height, width),
+        }
+    }
+
+    fn fit_to(&mut self, height: usize, width: usize, (screen_columns, screen_rows): (usize, usize), reserved: usize) {
+        self.columns = (1..=width).rev()
+            .find(|columns| line_width(*columns, width, height) <= screen_columns)
+            .unwrap_or(1);
+        // the header is on top of the rows, and the footer under them
+        let mut lines = screen_rows.saturating_sub(reserved + 2);
+        if lines < height * row_lines() + row_lines() - 1 || self.columns < width {
+            lines = lines.saturating_sub(MINIMAP_ROWS + 1);
+        }
+        // with rules between the rows there is one more under the last row
+        self.rows = (lines.saturating_sub(row_lines() - 1) / row_lines()).clamp(1, height);
+        self.clamp(height, width);
+    }
+
+    /// Moves the window, but it stays on the map
+    pub fn pan(&mut self, rows: isize, columns: isize, height: usize, width: usize) {
+        self.top = self.top.saturating_add_signed(rows);
+        self.left = self.left.saturating_add_signed(columns);
+        self.clamp(height, width);
+    }
+
+    /// Moves the window the least to have the tile in it
+    pub fn show(&mut self, row: usize, column: usize) {
+        if row < self.top {
+            self.top = row;
+        } else if row >= self.top + self.rows {
+            self.top = row + 1 - self.rows;
+        }
+        if column < self.left {
+            self.left = column;
+        } else if column >= self.left + self.columns {
+            self.left = column + 1 - self.columns;
+        }
+    }
+
+    /// Moves the window to have the tile in the middle
+    pub fn center(&mut self, row: usize, column: usize, height: usize, width: usize) {
+        self.top = row.saturating_sub(self.rows / 2);
+        self.left = column.saturating_sub(self.columns / 2);
+        self.clamp(height, width);
+    }
+
+    fn clamp(&mut self, height: usize, width: usize) {
+        self.top = self.top.min(height.saturating_sub(self.rows));
+        self.left = self.left.min(width.saturating_sub(self.columns));
+    }
+}
+
+/// A small picture of the whole map: '#' where there are tiles left to reveal, '.' where it is done
+///
+/// The part in the window is highlighted, and the last line tells which rows and columns are shown.
+pub fn minimap(mine_map: &[Vec<TileState>], view: &Viewport) -> Vec<Vec<StyledContent<String>>> {
+    let height = mine_map.len();
+    let width = mine_map[0].len();
+    let block_height = height.div_ceil(MINIMAP_ROWS);
+    let block_width = width.div_ceil(MINIMAP_COLUMNS);
+    let overlaps = |start: usize, size: usize, window: Range<usize>| start < window.end && window.start < start + size;
+
+    let mut lines = Vec::new();
+    for top in (0..height).step_by(block_height) {
+        let mut line = Vec::new();
+        for left in (0..width).step_by(block_width) {
+            let unfinished = mine_map[top..(top + block_height).min(height)].iter()
+                .any(|row| row[left..(left + block_width).min(width)].iter().any(|tile| match tile {
+                    TileState::HiddenEmpty(_) => true,
+                    TileState::Question(num) | TileState::Marked(num) => *num >= 0,
+                    _ => false,
+                }));
+            let text = if unfinished { "#" } else { "." };
+            if overlaps(top, block_height, view.row_range()) && overlaps(left, block_width, view.column_range()) {
+                line.push(paint_text(text, ContentStyle::new().reverse()));
+            } else {
+                line.push(text.to_string().stylize());
+            }
+        }
+        lines.push(line);
+    }
+    let scheme = coordinate_scheme();
+    lines.push(vec![trf("Columns {}-{} and rows {}-{} of the {}x{} map", &[
+        &scheme.column_name(view.left), &scheme.column_name(view.left + view.columns - 1),
+        &scheme.row_name(view.top), &scheme.row_name(view.top + view.rows - 1),
+        &width, &height]).stylize()]);
+    lines
+}
+
+/// Handles the 'pan' command: with a direction it moves the window, with a position it centers on it
+///
+/// Gives back `false` if the input is not a pan command.
+pub fn pan_command(input: &str, view: &mut Viewport, height: usize, width: usize) -> bool {
+    let input = input.trim().to_lowercase();
+    let mut words = input.split_whitespace();
+    if !words.next().is_some_and(|word| PAN_COMMANDS.contains(&word)) {
+        return false;
+    }
+    if view.is_whole(height, width) {
+        notify(tr("The whole map is on the screen already."));
+        return true;
+    }
+
+    let direction = words.next().unwrap_or("");
+    let steps = |page: usize| match words.clone().next() {
+        Some(number) => number.parse::<isize>().ok(),
+        None => Some((page / 2).max(1) as isize),
+    };
+    let moved = match direction {
+        "left" | "l" => steps(view.columns).map(|steps| view.pan(0, -steps, height, width)),
+        "right" | "r" => steps(view.columns).map(|steps| view.pan(0, steps, height, width)),
+        "up" | "u" => steps(view.rows).map(|steps| view.pan(-steps, 0, height, width)),
+        "down" | "d" => steps(view.rows).map(|steps| view.pan(steps, 0, height, width)),
+        position => parse_index(position).ok()
+            .filter(|(row, column)| (*row as usize) < height && (*column as usize) < width)
+            .map(|(row, column)| view.center(row as usize, column as usize, height, width)),
+    };
+    if moved.is_none() {
+        notify(tr("Type 'pan' with left, right, up or down and the number of tiles, or with a position to look at, like 'pan right 5' or 'pan C30'."));
+    }
+    true
+}
+
+#[test]
+fn fit_to_test() {
+    let mut view = Viewport::whole(5, 6);
+    view.fit_to(5, 6, (80, 24), 4);
+    assert_eq!(Viewport::whole(5, 6), view);
+
+    let mut view = Viewport::whole(30, 35);
+    view.fit_to(30, 35, (80, 24), 4);
+    assert!(view.columns < 35);
+    assert!(line_width(view.columns, 35, 30) <= 80);
+    assert!(line_width(view.columns + 1, 35, 30) > 80);
+    assert_eq!(24 - 4 - 2 - MINIMAP_ROWS - 1, view.rows);
+}
+
+#[test]
+fn pan_test() {
+    let mut view = Viewport { top: 0, left: 0, rows: 10, columns: 20 };
+    view.pan(-3, 5, 30, 35);
+    assert_eq!((0, 5), (view.top, view.left));
+    view.pan(100, 100, 30, 35);
+    assert_eq!((20, 15), (view.top, view.left));
+
+    view.show(2, 16);
+    assert_eq!((2, 15), (view.top, view.left));
+    view.show(29, 0);
+    assert_eq!((20, 0), (view.top, view.left));
+
+    view.center(15, 17, 30, 35);
+    assert_eq!((10, 7), (view.top, view.left));
+}
+
+#[test]
+fn pan_command_test() {
+    let mut view = Viewport { top: 0, left: 0, rows: 10, columns: 20 };
+    assert!(pan_command("pan right", &mut view, 30, 35));
+    assert_eq!(10, view.left);
+    assert!(pan_command("pan down 3", &mut view, 30, 35));
+    assert_eq!(3, view.top);
+    assert!(pan_command("view a1", &mut view, 30, 35));
+    assert_eq!((0, 0), (view.top, view.left));
+    assert!(pan_command("pan sideways", &mut view, 30, 35));
+    assert_eq!((0, 0), (view.top, view.left));
+    assert!(!pan_command("a1", &mut view, 30, 35));
+}
+
+#[test]
+fn minimap_test() {
+    let mut mine_map = vec![vec![TileState::VisibleEmpty(0); 48]; 12];
+    mine_map[11][47] = TileState::HiddenEmpty(1);
+    let view = Viewport { top: 0, left: 0, rows: 2, columns: 10 };
+    let lines: Vec<String> = minimap(&mine_map, &view).iter()
+        .map(|line| line.iter().map(|token| token.content().clone()).collect())
+        .collect();
+    assert_eq!(MINIMAP_ROWS + 1, lines.len());
+    assert_eq!(".".repeat(MINIMAP_COLUMNS), lines[0]);
+    assert!(lines[MINIMAP_ROWS - 1].ends_with('#'));
+    assert_eq!("Columns 1-10 and rows A-B of the 48x12 map", lines[MINIMAP_ROWS]);
+}